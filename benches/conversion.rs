@@ -0,0 +1,107 @@
+//! Compares the cost of converting raw ADC samples with the legacy per-sample mapping and with the
+//! precomputed [`Conversion`] coefficients. Run with `cargo bench --bench conversion`.
+//!
+//! On x86_64 the results are reported in TSC ticks per sample, which approximate CPU cycles. On
+//! other architectures they are reported in nanoseconds per sample.
+
+use std::hint::black_box;
+
+use ade791x::*;
+
+const SAMPLES: usize = 4096;
+const ROUNDS: usize = 200;
+
+/// Converts the raw measurement as done by `get_measurement()` before the coefficients were
+/// precomputed, mapping each channel to its full scale and applying offset and gain separately.
+fn legacy_convert(
+    raw: &RawMeasurement,
+    chip: Chip,
+    config: &Config,
+    cal: &Calibration,
+) -> Measurement {
+    fn map_adc(x: i32, out_min: f32, out_max: f32) -> f32 {
+        (x + 8_388_608) as f32 * (out_max - out_min) / 16_777_215.0 + out_min
+    }
+    let aux_offset = cal.offset.aux.unwrap_or(0.0);
+    let aux_gain = cal.gain.aux.unwrap_or(1.0);
+    let mut measurement = Measurement {
+        current: map_adc(raw.iwv, -49.27, 49.27),
+        voltage: map_adc(raw.v1wv, -788.0, 788.0),
+        aux: if chip == Chip::ADE7912 || config.temp_en {
+            MeasurementAux::Temperature(
+                aux_gain * raw.v2wv as f32 + 8.72101e-5 * aux_offset * 2048.0 - 306.47,
+            )
+        } else {
+            MeasurementAux::Voltage((map_adc(raw.v2wv, -788.0, 788.0) - aux_offset) * aux_gain)
+        },
+    };
+    measurement.current = (measurement.current - cal.offset.current) * cal.gain.current;
+    measurement.voltage = (measurement.voltage - cal.offset.voltage) * cal.gain.voltage;
+    measurement
+}
+
+#[cfg(target_arch = "x86_64")]
+fn now() -> u64 {
+    // SAFETY: `_rdtsc` has no preconditions on x86_64.
+    unsafe { core::arch::x86_64::_rdtsc() }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn now() -> u64 {
+    use std::sync::OnceLock;
+    use std::time::Instant;
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_nanos() as u64
+}
+
+/// Returns the minimum cost per sample of running `f` over the whole sample buffer.
+fn measure(samples: &[RawMeasurement], mut f: impl FnMut(&RawMeasurement) -> Measurement) -> f64 {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = now();
+            for raw in samples {
+                black_box(f(black_box(raw)));
+            }
+            (now() - start) as f64 / samples.len() as f64
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+fn main() {
+    let unit = if cfg!(target_arch = "x86_64") {
+        "ticks"
+    } else {
+        "ns"
+    };
+    let samples: Vec<RawMeasurement> = (0..SAMPLES as i32)
+        .map(|i| RawMeasurement {
+            iwv: (i * 2039) % 8_388_607,
+            v1wv: (i * 4093) % 8_388_607 - 4_000_000,
+            v2wv: 3_600_000 + i,
+        })
+        .collect();
+    let config = Config::default();
+    let calibration = Calibration {
+        offset: CalibrationOffset {
+            current: 0.01,
+            voltage: 0.2,
+            aux: Some(94.0),
+        },
+        gain: CalibrationGain {
+            current: 1.02,
+            voltage: 0.98,
+            aux: Some(8.72101e-5),
+        },
+        temperature: Some(TemperatureCalibration::from_tempos(94, false)),
+        tempco: CalibrationTempCo::default(),
+    };
+    for chip in [Chip::ADE7912, Chip::ADE7913] {
+        let conversion = Conversion::new(chip, &config, &calibration);
+        let legacy = measure(&samples, |raw| {
+            legacy_convert(raw, chip, &config, &calibration)
+        });
+        let precomputed = measure(&samples, |raw| conversion.convert(raw));
+        println!("{chip:?} legacy:      {legacy:>6.2} {unit}/sample");
+        println!("{chip:?} precomputed: {precomputed:>6.2} {unit}/sample");
+    }
+}
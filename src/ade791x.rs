@@ -6,9 +6,21 @@ use super::*;
 pub(crate) struct Ade791x<SPI, CS> {
     _spi: PhantomData<SPI>,
     cs: CS,
+    index: usize,
     chip: Chip,
     config: Config,
+    emi_ctrl: EmiCtrl,
     calibration: Calibration,
+    conversion: Conversion,
+    temperature_coefficients: Coefficients,
+    tempos: i8,
+    scheduler: Option<TemperatureScheduler>,
+    temperature: Option<f32>,
+    channels: Channels,
+    retry: RetryPolicy,
+    retry_counters: RetryCounters,
+    powered_down: bool,
+    protection: bool,
 }
 
 impl<SPI, CS, S, P> Ade791x<SPI, CS>
@@ -21,13 +33,26 @@ where
     /// # Arguments
     /// * `cs` - The CS output pin implementing the [`OutputPin`] trait.
     /// * `chip` - The chip version as a [`Chip`].
-    pub fn new(cs: CS, chip: Chip) -> Self {
+    /// * `index` - The index of the ADC in the polyphase metering system, reported in the errors.
+    pub fn new(cs: CS, chip: Chip, index: usize) -> Self {
         Self {
             _spi: PhantomData,
             chip,
             cs,
+            index,
             config: Config::default(),
+            emi_ctrl: EmiCtrl::default(),
             calibration: Calibration::default(),
+            conversion: Conversion::new(chip, &Config::default(), &Calibration::default()),
+            temperature_coefficients: TemperatureCalibration::from_tempos(0, false).coefficients(),
+            tempos: 0,
+            scheduler: None,
+            temperature: None,
+            channels: Channels::default(),
+            retry: RetryPolicy::default(),
+            retry_counters: RetryCounters::default(),
+            powered_down: false,
+            protection: true,
         }
     }
 
@@ -39,7 +64,7 @@ where
     /// * `config` - The [`Config`] struct containing the configuration for the ADC.
     /// * `calibration` - The [`Calibration`] struct containing the calibration values for the ADC.
     /// * `emi_ctrl` - The [`EmiCtrl`] struct containing the EMI settings for polyphase
-    /// configurations.
+    ///   configurations.
     pub fn init(
         &mut self,
         spi: &mut SPI,
@@ -49,35 +74,116 @@ where
         emi_ctrl: EmiCtrl,
     ) -> Result<(), Error<S, P>> {
         self.config = config;
-        self.calibration = calibration;
+        self.emi_ctrl = emi_ctrl;
+        self.powered_down = false;
         self.wait_reset(spi, delay)?;
-        self.write_reg_checked(spi, Register::Config, self.config.into())?;
-        self.write_reg_checked(spi, Register::EmiCtrl, emi_ctrl.into())?;
-        if self.calibration.offset.aux.is_none() {
-            self.calibration.offset.aux = if self.config.temp_en || self.chip == Chip::ADE7912 {
-                Some(self.read_reg(spi, Register::Tempos)?[1] as i8 as f32)
-            } else {
-                Some(0.0)
-            };
+        let config = self.config.into();
+        self.with_retry(spi, Some(&mut *delay), |adc, spi| {
+            adc.write_reg_checked(spi, Register::Config, config)
+        })?;
+        let emi_ctrl = self.emi_ctrl.into();
+        self.with_retry(spi, Some(&mut *delay), |adc, spi| {
+            adc.write_reg_checked(spi, Register::EmiCtrl, emi_ctrl)
+        })?;
+        if self.is_temperature() {
+            self.tempos = self.with_retry(spi, Some(&mut *delay), |adc, spi| {
+                adc.read_reg(spi, Register::Tempos)
+            })?[1] as i8;
         }
-        if self.calibration.gain.aux.is_none() {
-            self.calibration.gain.aux = if self.config.temp_en || self.chip == Chip::ADE7912 {
-                if self.config.bw {
-                    Some(8.21015e-5)
-                } else {
-                    Some(8.72101e-5)
-                }
-            } else {
-                Some(1.0)
-            }
+        self.set_calibration(calibration);
+        Ok(())
+    }
+
+    /// Sets new calibration values for the ADC, updating the precomputed conversion coefficients.
+    /// A temperature calibration set to [`None`] is resolved based on the bandwidth setting and on
+    /// the TEMPOS value read during [`Self::init()`].
+    /// # Arguments
+    /// * `calibration` - The [`Calibration`] struct containing the calibration values for the ADC.
+    pub fn set_calibration(&mut self, calibration: Calibration) {
+        self.calibration = calibration;
+        let resolved = self.resolved_calibration();
+        self.conversion = Conversion::new(self.chip, &self.config, &resolved);
+        if let Some(temperature) = resolved.temperature {
+            self.temperature_coefficients = temperature.coefficients();
+        }
+    }
+
+    /// Returns the calibration values of the ADC, with the temperature calibration resolved based
+    /// on the bandwidth setting and on the TEMPOS value if it was set to [`None`].
+    pub fn resolved_calibration(&self) -> Calibration {
+        let mut calibration = self.calibration;
+        if calibration.temperature.is_none() {
+            calibration.temperature = Some(TemperatureCalibration::from_tempos(
+                self.tempos,
+                self.config.bw,
+            ));
+        }
+        calibration
+    }
+
+    /// Sets the schedule of the interleaved temperature sampling. The schedule is only permitted
+    /// for the ADE7913 measuring the second voltage, with the second voltage channel read as
+    /// selected with [`Self::set_channels()`], otherwise a
+    /// [`Error::TemperatureScheduleNotPermitted`] is returned. The TEMPOS register is read to
+    /// calibrate the temperature sensor.
+    /// # Arguments
+    /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
+    /// * `schedule` - The [`TemperatureSchedule`] to follow, or [`None`] to disable it.
+    pub fn set_temperature_schedule(
+        &mut self,
+        spi: &mut SPI,
+        schedule: Option<TemperatureSchedule>,
+    ) -> Result<(), Error<S, P>> {
+        let Some(schedule) = schedule else {
+            self.scheduler = None;
+            return Ok(());
+        };
+        if self.is_temperature() || !self.channels.has_aux() {
+            return Err(Error::TemperatureScheduleNotPermitted { device: self.index });
         }
+        self.tempos = self.read_reg(spi, Register::Tempos)?[1] as i8;
+        self.set_calibration(self.calibration);
+        self.scheduler = Some(TemperatureScheduler::new(schedule));
         Ok(())
     }
 
-    /// Returns `true` if the ADC is generating the DREADY signal, `false` if it is generating the
-    /// CLKOUT signal instead.
-    pub fn is_dr_source(&self) -> bool {
-        !self.config.clkout_en
+    /// Returns the latest temperature measured by the interleaved temperature sampling, if any,
+    /// in °C. Each temperature is returned only once.
+    pub fn take_temperature(&mut self) -> Option<f32> {
+        self.scheduler
+            .as_mut()
+            .and_then(|scheduler| scheduler.take())
+    }
+
+    /// Returns the precomputed [`Conversion`] used by [`Self::get_measurement()`].
+    pub fn conversion(&self) -> Conversion {
+        self.conversion
+    }
+
+    /// Returns the chip version as a [`Chip`].
+    pub fn chip(&self) -> Chip {
+        self.chip
+    }
+
+    /// Returns the configuration of the ADC as a [`Config`].
+    pub fn config(&self) -> Config {
+        self.config
+    }
+
+    /// Returns the channels read by [`Self::get_raw_measurement()`] as a [`Channels`] value.
+    pub fn channels(&self) -> Channels {
+        self.channels
+    }
+
+    /// Returns the EMI settings of the ADC as an [`EmiCtrl`].
+    pub fn emi_ctrl(&self) -> EmiCtrl {
+        self.emi_ctrl
+    }
+
+    /// Returns `true` if the auxiliary channel of the ADC is measuring the temperature, `false` if
+    /// it is measuring a voltage.
+    pub fn is_temperature(&self) -> bool {
+        self.config.temp_en || self.chip == Chip::ADE7912
     }
 
     /// Performs a hardware reset of the ADC. During a hardware reset, all the registers are set to
@@ -102,11 +208,13 @@ where
             swrst: true,
             ..Default::default()
         };
-        self.write_reg(spi, Register::Config, config.into())
+        self.with_retry(spi, None, |adc, spi| {
+            adc.write_reg(spi, Register::Config, config.into())
+        })
     }
 
     /// Waits for the reset (either hardware or software) to be completed. The function timeouts
-    /// returning a [`Error::ResetError`] after about 500 ms.
+    /// returning a [`Error::ResetTimeout`] after about 500 ms.
     /// # Arguments
     /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
     /// * `delay` - The delay source implementing the [`DelayMs`] trait.
@@ -116,12 +224,15 @@ where
         delay: &mut dyn DelayMs<u32>,
     ) -> Result<(), Error<S, P>> {
         for _ in 0..5 {
-            if !Status0::from(self.read_reg(spi, Register::Status0)?[1]).reset_on {
+            let status0 = self.with_retry(spi, Some(&mut *delay), |adc, spi| {
+                adc.read_reg(spi, Register::Status0)
+            })?;
+            if !Status0::from(status0[1]).reset_on {
                 return Ok(());
             }
             delay.delay_ms(100);
         }
-        Err(Error::ResetTimeout)
+        Err(Error::ResetTimeout { device: self.index })
     }
 
     /// Powers-down the ADC by turning off the dc-to-dc converter and shutting down the Σ-Δ
@@ -130,17 +241,43 @@ where
     /// # Arguments
     /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
     pub fn powerdown(&mut self, spi: &mut SPI) -> Result<(), Error<S, P>> {
-        let mut config = self.config;
-        config.pwrdwn_en = true;
-        config.clkout_en = false;
-        self.write_reg(spi, Register::Config, config.into())
+        let content = self.powerdown_config().into();
+        self.with_retry(spi, None, |adc, spi| {
+            adc.write_reg(spi, Register::Config, content)
+        })?;
+        self.powered_down = true;
+        Ok(())
+    }
+
+    /// Returns the configuration written by [`Self::powerdown()`], derived from the stored one with
+    /// the power-down enabled and the CLKOUT disabled.
+    fn powerdown_config(&self) -> Config {
+        Config {
+            pwrdwn_en: true,
+            clkout_en: false,
+            ..self.config
+        }
     }
 
     /// Wakes-up the ADC by turning on the dc-to-dc converter and activating the Σ-Δ modulators.
     /// # Arguments
     /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
     pub fn wakeup(&mut self, spi: &mut SPI) -> Result<(), Error<S, P>> {
-        self.write_reg(spi, Register::Config, self.config.into())
+        if let Some(scheduler) = &mut self.scheduler {
+            scheduler.restart();
+        }
+        let content = self.config.into();
+        self.with_retry(spi, None, |adc, spi| {
+            adc.write_reg(spi, Register::Config, content)
+        })?;
+        self.powered_down = false;
+        Ok(())
+    }
+
+    /// Returns `true` if the ADC has been powered-down with [`Self::powerdown()`], `false`
+    /// otherwise.
+    pub fn is_powered_down(&self) -> bool {
+        self.powered_down
     }
 
     /// Starts listening for a broadcast send on the SPI bus.
@@ -197,8 +334,10 @@ where
         if !(-1..=1).contains(&drift) {
             let adj = if c > cref { cref + c0 - c } else { cref - c };
             let bytes = adj.to_be_bytes();
-            self.write_reg(spi, Register::Counter0, bytes[1])?;
-            self.write_reg(spi, Register::Counter1, bytes[0])?;
+            self.with_retry(spi, None, |adc, spi| {
+                adc.write_reg(spi, Register::Counter0, bytes[1])?;
+                adc.write_reg(spi, Register::Counter1, bytes[0])
+            })?;
         }
         Ok(drift)
     }
@@ -207,71 +346,336 @@ where
     /// # Arguments
     /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
     pub fn lock(&mut self, spi: &mut SPI) -> Result<(), Error<S, P>> {
-        self.write_reg(spi, Register::Lock, LockOp::Enable as u8)
+        self.with_retry(spi, None, |adc, spi| {
+            adc.write_reg(spi, Register::Lock, LockOp::Enable as u8)
+        })
     }
 
     /// Unlocks the internal register of the ADC, meaning that they can be written.
     /// # Arguments
     /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
     pub fn unlock(&mut self, spi: &mut SPI) -> Result<(), Error<S, P>> {
-        self.write_reg(spi, Register::Lock, LockOp::Disable as u8)
+        self.with_retry(spi, None, |adc, spi| {
+            adc.write_reg(spi, Register::Lock, LockOp::Disable as u8)
+        })
+    }
+
+    /// Locks the internal registers of the ADC and verifies the `ic_prot` bit of the STATUS0
+    /// register, returning a [`Error::LockFailed`] if it is cleared. Does nothing if the protection
+    /// is disabled.
+    /// # Arguments
+    /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
+    pub fn relock(&mut self, spi: &mut SPI) -> Result<(), Error<S, P>> {
+        if !self.protection {
+            return Ok(());
+        }
+        self.lock(spi)?;
+        if !self.is_locked(spi)? {
+            return Err(Error::LockFailed { device: self.index });
+        }
+        Ok(())
+    }
+
+    /// Sets whether the internal registers of the ADC are locked after the writings performed
+    /// outside of a configuration session, i.e. by the temperature schedule.
+    /// # Arguments
+    /// * `enabled` - `true` to lock the registers, `false` to leave them unlocked.
+    pub fn set_protection(&mut self, enabled: bool) {
+        self.protection = enabled;
+    }
+
+    /// Returns `true` if the internal registers of the ADC are locked, reading the `ic_prot` bit
+    /// of the STATUS0 register.
+    /// # Arguments
+    /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
+    pub fn is_locked(&mut self, spi: &mut SPI) -> Result<bool, Error<S, P>> {
+        let status0 =
+            self.with_retry(spi, None, |adc, spi| adc.read_reg(spi, Register::Status0))?;
+        Ok(Status0::from(status0[1]).ic_prot)
+    }
+
+    /// Writes a new configuration to the unlocked ADC with a checked write, updating the
+    /// precomputed conversion coefficients. The TEMPOS register is read again if the auxiliary
+    /// channel is measuring the temperature.
+    /// # Arguments
+    /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
+    /// * `config` - The [`Config`] struct containing the configuration for the ADC.
+    pub fn write_config(&mut self, spi: &mut SPI, config: Config) -> Result<(), Error<S, P>> {
+        let content = config.into();
+        self.with_retry(spi, None, |adc, spi| {
+            adc.write_reg_checked(spi, Register::Config, content)
+        })?;
+        self.config = config;
+        if self.is_temperature() {
+            self.tempos = self
+                .with_retry(spi, None, |adc, spi| adc.read_reg(spi, Register::Tempos))?[1]
+                as i8;
+        }
+        self.set_calibration(self.calibration);
+        Ok(())
+    }
+
+    /// Writes new EMI settings to the unlocked ADC with a checked write.
+    /// # Arguments
+    /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
+    /// * `emi_ctrl` - The [`EmiCtrl`] struct containing the EMI settings for the ADC.
+    pub fn write_emi_ctrl(&mut self, spi: &mut SPI, emi_ctrl: EmiCtrl) -> Result<(), Error<S, P>> {
+        let content = emi_ctrl.into();
+        self.with_retry(spi, None, |adc, spi| {
+            adc.write_reg_checked(spi, Register::EmiCtrl, content)
+        })?;
+        self.emi_ctrl = emi_ctrl;
+        Ok(())
     }
 
     /// Returns the value of the snapshot of the internal counter, triggered with [`Self::snap()`].
     /// # Arguments
     /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
     pub fn get_cnt_snapshot(&mut self, spi: &mut SPI) -> Result<u16, Error<S, P>> {
-        Ok(BurstRead::from(self.burst_read(spi, Register::CntSnapshot, 2)?).cnt_snapshot)
+        let burst_read = self.with_retry(spi, None, |adc, spi| {
+            adc.burst_read(spi, Register::CntSnapshot, 2)
+        })?;
+        Ok(BurstRead::from(burst_read).cnt_snapshot)
+    }
+
+    /// Checks that the given channels can be selected with [`Self::set_channels()`]: the second
+    /// voltage channel must be read while an interleaved temperature sampling is active, otherwise
+    /// a [`Error::TemperatureScheduleNotPermitted`] is returned.
+    /// # Arguments
+    /// * `channels` - The set of channels to read as a [`Channels`] value.
+    pub fn check_channels(&self, channels: Channels) -> Result<(), Error<S, P>> {
+        if self.scheduler.is_some() && !channels.has_aux() {
+            return Err(Error::TemperatureScheduleNotPermitted { device: self.index });
+        }
+        Ok(())
+    }
+
+    /// Selects the channels read by [`Self::get_raw_measurement()`] and
+    /// [`Self::get_measurement()`], checked with [`Self::check_channels()`].
+    /// # Arguments
+    /// * `channels` - The set of channels to read as a [`Channels`] value.
+    pub fn set_channels(&mut self, channels: Channels) {
+        self.channels = channels;
     }
 
     /// Returns the latest available measurement from the ADC, without applying any conversion, as
-    /// a [`RawMeasurement`] struct.
+    /// a [`RawMeasurement`] struct. Only the channels selected with [`Self::set_channels()`] are
+    /// read, the others are set to zero. If the CRC is read as well, it is checked against the
+    /// received data, returning a [`Error::CrcMismatch`] if they do not match.
     /// # Arguments
     /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
     pub fn get_raw_measurement(&mut self, spi: &mut SPI) -> Result<RawMeasurement, Error<S, P>> {
-        let burst_read = BurstRead::from(self.burst_read(spi, Register::Iwv, 9)?);
-        Ok(RawMeasurement {
+        self.with_retry(spi, None, Self::read_raw_measurement)
+    }
+
+    /// Performs a single attempt of [`Self::get_raw_measurement()`].
+    /// # Arguments
+    /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
+    fn read_raw_measurement(&mut self, spi: &mut SPI) -> Result<RawMeasurement, Error<S, P>> {
+        let burst_read =
+            BurstRead::from(self.burst_read(spi, Register::Iwv, self.channels.burst_len())?);
+        let raw_measurement = RawMeasurement {
             iwv: burst_read.iwv,
             v1wv: burst_read.v1wv,
             v2wv: burst_read.v2wv,
+        };
+        if self.channels == Channels::AllCrc {
+            let crc = raw_measurement.crc();
+            if crc != burst_read.adc_crc {
+                return Err(Error::CrcMismatch {
+                    device: self.index,
+                    expected: crc,
+                    actual: burst_read.adc_crc,
+                });
+            }
+        }
+        Ok(raw_measurement)
+    }
+
+    /// Returns the latest available sample from the ADC as a [`Sample`] struct, reading the
+    /// waveforms, the ADC CRC, the STATUS0 register and the counter snapshot in a single burst
+    /// transaction.
+    /// # Arguments
+    /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
+    pub fn get_sample(&mut self, spi: &mut SPI) -> Result<Sample, Error<S, P>> {
+        let burst_read = BurstRead::from(
+            self.with_retry(spi, None, |adc, spi| adc.burst_read(spi, Register::Iwv, 14))?,
+        );
+        Ok(Sample {
+            raw: RawMeasurement {
+                iwv: burst_read.iwv,
+                v1wv: burst_read.v1wv,
+                v2wv: burst_read.v2wv,
+            },
+            adc_crc: burst_read.adc_crc,
+            status0: burst_read.status0,
+            cnt_snapshot: burst_read.cnt_snapshot,
         })
     }
 
+    /// Reads every readable register of the ADC into a [`dump::RegisterDump`] struct.
+    /// # Arguments
+    /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
+    pub fn dump_registers(&mut self, spi: &mut SPI) -> Result<dump::RegisterDump, Error<S, P>> {
+        self.with_retry(spi, None, |adc, spi| adc.read_dump(spi))
+    }
+
+    /// Reads every readable register of the ADC once, see [`Self::dump_registers()`].
+    /// # Arguments
+    /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
+    fn read_dump(&mut self, spi: &mut SPI) -> Result<dump::RegisterDump, Error<S, P>> {
+        let burst_read = BurstRead::from(self.burst_read(spi, Register::Iwv, 14)?);
+        let ctrl_crc = self.read_reg_u16(spi, Register::CtrlCrc)?;
+        Ok(dump::RegisterDump {
+            iwv: burst_read.iwv,
+            v1wv: burst_read.v1wv,
+            v2wv: burst_read.v2wv,
+            adc_crc: burst_read.adc_crc,
+            ctrl_crc,
+            cnt_snapshot: burst_read.cnt_snapshot,
+            config: Config::from(self.read_reg(spi, Register::Config)?[1]),
+            status0: burst_read.status0,
+            status1: Status1::from(self.read_reg(spi, Register::Status1)?[1]),
+            emi_ctrl: EmiCtrl::from(self.read_reg(spi, Register::EmiCtrl)?[1]),
+            tempos: self.read_reg(spi, Register::Tempos)?[1] as i8,
+        })
+    }
+
+    /// Returns the expected content of the configuration and status registers of the ADC, derived
+    /// from the stored configuration, as a [`dump::Expected`] struct. The CONFIG register is
+    /// expected as written by [`Self::powerdown()`] if the ADC is powered-down, and with the
+    /// `temp_en` field set by the temperature schedule, if any, otherwise.
+    /// # Arguments
+    /// * `locked` - `true` if the configuration registers are expected to be protected.
+    pub fn expected_registers(&self, locked: bool) -> dump::Expected {
+        let config = match &self.scheduler {
+            _ if self.powered_down => self.powerdown_config(),
+            Some(scheduler) => Config {
+                temp_en: scheduler.temp_en(),
+                ..self.config
+            },
+            None => self.config,
+        };
+        dump::Expected {
+            config,
+            emi_ctrl: self.emi_ctrl,
+            locked,
+        }
+    }
+
+    /// Sets the [`RetryPolicy`] applied to the transactions of the ADC.
+    /// # Arguments
+    /// * `retry` - The [`RetryPolicy`] struct to apply.
+    pub fn set_retry_policy(&mut self, retry: RetryPolicy) {
+        self.retry = retry;
+    }
+
+    /// Returns the [`RetryPolicy`] applied to the transactions of the ADC.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry
+    }
+
+    /// Returns the [`RetryCounters`] of the ADC.
+    pub fn retry_counters(&self) -> RetryCounters {
+        self.retry_counters
+    }
+
+    /// Resets the [`RetryCounters`] of the ADC to zero.
+    pub fn reset_retry_counters(&mut self) {
+        self.retry_counters = RetryCounters::default();
+    }
+
     /// Returns the latest available measurement from the ADC as a [`Measurement`] struct.
     /// # Arguments
     /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
     pub fn get_measurement(&mut self, spi: &mut SPI) -> Result<Measurement, Error<S, P>> {
         let raw_measurement = self.get_raw_measurement(spi)?;
-        let aux_offset = self.calibration.offset.aux.unwrap_or(0.0);
-        let aux_gain = self.calibration.gain.aux.unwrap_or(1.0);
-        let mut measurement = Measurement {
-            current: Self::map_adc(raw_measurement.iwv, -49.27, 49.27),
-            voltage: Self::map_adc(raw_measurement.v1wv, -788.0, 788.0),
-            aux: if self.chip == Chip::ADE7912 || self.config.temp_en {
-                MeasurementAux::Temperature(
-                    aux_gain * raw_measurement.v2wv as f32 + 8.72101e-5 * aux_offset * 2048.0
-                        - 306.47,
-                )
-            } else {
-                MeasurementAux::Voltage(
-                    (Self::map_adc(raw_measurement.v2wv, -788.0, 788.0) - aux_offset) * aux_gain,
-                )
-            },
-        };
-        measurement.current =
-            (measurement.current - self.calibration.offset.current) * self.calibration.gain.current;
-        measurement.voltage =
-            (measurement.voltage - self.calibration.offset.voltage) * self.calibration.gain.voltage;
+        self.convert(spi, &raw_measurement)
+    }
+
+    /// Converts a [`RawMeasurement`] struct read from the ADC to a [`Measurement`] struct, updating
+    /// the temperature and the interleaved temperature sampling. The fields of the channels that are
+    /// not read, as selected with [`Self::set_channels()`], are flagged as unavailable, so the
+    /// temperature is only updated if the auxiliary channel is read.
+    /// # Arguments
+    /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
+    /// * `raw_measurement` - The [`RawMeasurement`] struct read from the ADC.
+    pub fn convert(
+        &mut self,
+        spi: &mut SPI,
+        raw_measurement: &RawMeasurement,
+    ) -> Result<Measurement, Error<S, P>> {
+        let mut measurement = self.conversion.convert(raw_measurement);
+        self.channels.mask(&mut measurement);
+        if let MeasurementAux::Temperature(temperature) = measurement.aux {
+            self.temperature = Some(temperature);
+        }
+        self.step_scheduler(spi, raw_measurement.v2wv, &mut measurement)?;
+        if let Some(temperature) = self.temperature {
+            self.calibration
+                .tempco
+                .compensate(temperature, &mut measurement);
+        }
         Ok(measurement)
     }
 
-    /// Returns the given ADC value mapped between the provided output values.
+    /// Advances the interleaved temperature sampling by one sample, if active, flagging the
+    /// auxiliary field of the measurement if it is not a valid voltage sample, and switching the
+    /// second voltage channel if requested by the schedule.
     /// # Arguments
-    /// * `x`: The ADC value to be mapped.
-    /// * `out_min`: The minimum value of the mapping output.
-    /// * `out_max`: The maximum value of the mapping output.
-    fn map_adc(x: i32, out_min: f32, out_max: f32) -> f32 {
-        (x + 8_388_608) as f32 * (out_max - out_min) / 16_777_215.0 + out_min
+    /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
+    /// * `v2wv` - The raw value of the V2WV register.
+    /// * `measurement` - The [`Measurement`] converted from the sample.
+    fn step_scheduler(
+        &mut self,
+        spi: &mut SPI,
+        v2wv: i32,
+        measurement: &mut Measurement,
+    ) -> Result<(), Error<S, P>> {
+        let Some(scheduler) = self.scheduler.as_mut() else {
+            return Ok(());
+        };
+        let previous = scheduler.clone();
+        let (valid, switch) = scheduler.step(v2wv, &self.temperature_coefficients);
+        if !valid {
+            measurement.aux = MeasurementAux::Unavailable;
+        }
+        let temp_en = match switch {
+            Switch::None => return Ok(()),
+            Switch::ToTemperature => true,
+            Switch::ToVoltage => false,
+        };
+        let content = Config {
+            temp_en,
+            ..self.config
+        }
+        .into();
+        if self.protection {
+            self.unlock(spi)
+                .inspect_err(|_| self.scheduler = Some(previous.clone()))?;
+        }
+        let result = self.with_retry(spi, None, |adc, spi| {
+            adc.write_reg_checked(spi, Register::Config, content)
+        });
+        if let Err(e) = result {
+            // The channel has not been switched, so the switch is attempted again on the next sample
+            self.scheduler = Some(previous);
+            if self.protection {
+                self.lock(spi).ok();
+            }
+            return Err(e);
+        }
+        if let (Switch::ToVoltage, Some(scheduler)) = (switch, &self.scheduler) {
+            self.temperature = scheduler.peek();
+        }
+        self.relock(spi)
+    }
+
+    /// Returns the raw value of the V2WV register, reading only the auxiliary channel.
+    /// # Arguments
+    /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
+    pub fn get_raw_aux(&mut self, spi: &mut SPI) -> Result<i32, Error<S, P>> {
+        Ok(BurstRead::from(self.burst_read(spi, Register::V2wv, 3)?).v2wv)
     }
 
     /// Performs a burst read on the SPI bus. This operation is used to get multiple register values
@@ -297,7 +701,7 @@ where
             Register::AdcCrc => 10,
             Register::Status0 => 12,
             Register::CntSnapshot => 13,
-            _ => return Err(Error::BurstReadNotPermitted),
+            _ => return Err(Error::BurstReadNotPermitted(start_reg)),
         };
         let mut bytes = [0; 15];
         bytes[0] = (start_reg.addr() << 3) | SpiOp::Read as u8;
@@ -317,10 +721,33 @@ where
     /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
     /// * `reg` - The register as a [`Register`] value.
     fn read_reg(&mut self, spi: &mut SPI, reg: Register) -> Result<[u8; 2], Error<S, P>> {
+        self.read_reg_bytes(spi, reg)
+    }
+
+    /// Performs a register reading of a 16-bit register, returning its content.
+    /// # Arguments
+    /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
+    /// * `reg` - The register as a [`Register`] value.
+    fn read_reg_u16(&mut self, spi: &mut SPI, reg: Register) -> Result<u16, Error<S, P>> {
+        let bytes: [u8; 3] = self.read_reg_bytes(spi, reg)?;
+        Ok(u16::from_be_bytes([bytes[1], bytes[2]]))
+    }
+
+    /// Performs a register reading of `L - 1` response bytes, returning the whole transaction
+    /// buffer with the first byte representing the command byte.
+    /// # Arguments
+    /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
+    /// * `reg` - The register as a [`Register`] value.
+    fn read_reg_bytes<const L: usize>(
+        &mut self,
+        spi: &mut SPI,
+        reg: Register,
+    ) -> Result<[u8; L], Error<S, P>> {
         if reg.is_write_only() {
-            return Err(Error::WriteOnlyRegister);
+            return Err(Error::WriteOnlyRegister(reg));
         }
-        let mut bytes = [(reg.addr() << 3) | SpiOp::Read as u8, 0];
+        let mut bytes = [0; L];
+        bytes[0] = (reg.addr() << 3) | SpiOp::Read as u8;
         self.cs.set_low().map_err(Error::PinError)?;
         spi.transfer(&mut bytes).map_err(Error::SpiError)?;
         self.cs.set_high().map_err(Error::PinError)?;
@@ -335,7 +762,7 @@ where
     /// * `content` - The content to write to the register as an `u8` value.
     fn write_reg(&mut self, spi: &mut SPI, reg: Register, content: u8) -> Result<(), Error<S, P>> {
         if reg.is_read_only() {
-            return Err(Error::ReadOnlyRegister);
+            return Err(Error::ReadOnlyRegister(reg));
         }
         let mut bytes = [(reg.addr() << 3) | SpiOp::Write as u8, content];
         self.cs.set_low().map_err(Error::PinError)?;
@@ -358,9 +785,59 @@ where
         content: u8,
     ) -> Result<(), Error<S, P>> {
         self.write_reg(spi, reg, content)?;
-        if self.read_reg(spi, reg)?[1] != content {
-            return Err(Error::RegisterContentMismatch);
+        let actual = self.read_reg(spi, reg)?[1];
+        if actual != content {
+            return Err(Error::RegisterContentMismatch {
+                device: self.index,
+                register: reg,
+                expected: content,
+                actual,
+            });
         }
         Ok(())
     }
+
+    /// Performs the given transaction, repeating it according to the [`RetryPolicy`] of the ADC and
+    /// updating the [`RetryCounters`]. The CS pin is released before each new attempt, as a failed
+    /// transaction may leave it low.
+    /// # Arguments
+    /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
+    /// * `delay` - The delay source implementing the [`DelayMs`] trait, used for the backoff
+    ///   between the attempts, or [`None`] to retry immediately.
+    /// * `op` - The transaction to perform.
+    fn with_retry<T>(
+        &mut self,
+        spi: &mut SPI,
+        mut delay: Option<&mut dyn DelayMs<u32>>,
+        mut op: impl FnMut(&mut Self, &mut SPI) -> Result<T, Error<S, P>>,
+    ) -> Result<T, Error<S, P>> {
+        let mut attempt = 1;
+        loop {
+            match op(self, spi) {
+                Ok(x) => {
+                    if attempt > 1 {
+                        self.retry_counters.recovered =
+                            self.retry_counters.recovered.wrapping_add(1);
+                    }
+                    return Ok(x);
+                }
+                Err(e) if self.retry.is_retryable(&e) => {
+                    if attempt >= self.retry.max_attempts {
+                        if attempt > 1 {
+                            self.retry_counters.exhausted =
+                                self.retry_counters.exhausted.wrapping_add(1);
+                        }
+                        return Err(e);
+                    }
+                    attempt += 1;
+                    self.retry_counters.retries = self.retry_counters.retries.wrapping_add(1);
+                    self.cs.set_high().map_err(Error::PinError)?;
+                    if let Some(delay) = delay.as_deref_mut() {
+                        delay.delay_ms(self.retry.backoff_ms);
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
 }
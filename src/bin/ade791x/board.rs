@@ -0,0 +1,179 @@
+//! TOML description of the board, listing the SPI device and the ADCs wired to it.
+//!
+//! ```toml
+//! spi = "/dev/spidev0.0"
+//! speed_hz = 1000000
+//!
+//! [[device]]
+//! chip = "ADE7913"
+//! cs = 8
+//! clkout_en = true
+//!
+//! [[device]]
+//! chip = "ADE7912"
+//! cs = 7
+//!
+//! [device.calibration]
+//! offset_current = 0.012
+//! gain_current = 1.02
+//! ```
+
+use std::path::Path;
+
+use ade791x::*;
+use serde::Deserialize;
+
+/// Describes the board.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Board {
+    /// Path of the spidev device.
+    #[serde(default = "default_spi")]
+    pub spi: String,
+    /// Clock frequency of the SPI bus in Hz.
+    #[serde(default = "default_speed_hz")]
+    pub speed_hz: u32,
+    /// ADCs wired to the SPI bus.
+    #[serde(rename = "device")]
+    pub devices: Vec<Device>,
+}
+
+/// Describes an ADC of the board.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Device {
+    /// Chip of the ADC.
+    pub chip: ChipName,
+    /// Sysfs GPIO number of the CS pin.
+    #[serde(default)]
+    pub cs: u64,
+    /// Enables the CLKOUT functionality at the CLKOUT/DREADY pin.
+    #[serde(default)]
+    pub clkout_en: bool,
+    /// Measures the temperature on the second voltage channel.
+    #[serde(default)]
+    pub temp_en: bool,
+    /// ADC output frequency in Hz.
+    #[serde(default = "default_adc_freq")]
+    pub adc_freq: u32,
+    /// Selects the 2 kHz bandwidth of the digital low-pass filter.
+    #[serde(default)]
+    pub bw: bool,
+    /// Calibration values of the ADC.
+    #[serde(default)]
+    pub calibration: CalibrationValues,
+}
+
+/// Represents the chip names accepted in the description.
+#[derive(Debug, Copy, Clone, Deserialize)]
+pub enum ChipName {
+    ADE7912,
+    ADE7913,
+}
+
+/// Contains the calibration values of an ADC.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CalibrationValues {
+    #[serde(default)]
+    pub offset_current: f32,
+    #[serde(default)]
+    pub offset_voltage: f32,
+    pub offset_aux: Option<f32>,
+    #[serde(default = "default_gain")]
+    pub gain_current: f32,
+    #[serde(default = "default_gain")]
+    pub gain_voltage: f32,
+    pub gain_aux: Option<f32>,
+}
+
+impl Default for CalibrationValues {
+    fn default() -> Self {
+        Self {
+            offset_current: 0.0,
+            offset_voltage: 0.0,
+            offset_aux: None,
+            gain_current: 1.0,
+            gain_voltage: 1.0,
+            gain_aux: None,
+        }
+    }
+}
+
+fn default_spi() -> String {
+    "/dev/spidev0.0".into()
+}
+
+fn default_speed_hz() -> u32 {
+    1_000_000
+}
+
+fn default_adc_freq() -> u32 {
+    8000
+}
+
+fn default_gain() -> f32 {
+    1.0
+}
+
+impl Board {
+    /// Reads the description from the given TOML file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+        let board: Board =
+            toml::from_str(&text).map_err(|e| format!("invalid {}: {e}", path.display()))?;
+        for device in &board.devices {
+            device.adc_freq()?;
+        }
+        Ok(board)
+    }
+}
+
+impl Device {
+    pub fn chip(&self) -> Chip {
+        match self.chip {
+            ChipName::ADE7912 => Chip::ADE7912,
+            ChipName::ADE7913 => Chip::ADE7913,
+        }
+    }
+
+    pub fn adc_freq(&self) -> Result<AdcFreqVal, String> {
+        match self.adc_freq {
+            8000 => Ok(AdcFreqVal::KHz8),
+            4000 => Ok(AdcFreqVal::KHz4),
+            2000 => Ok(AdcFreqVal::KHz2),
+            1000 => Ok(AdcFreqVal::KHz1),
+            x => Err(format!(
+                "invalid adc_freq {x}, expected 8000, 4000, 2000 or 1000"
+            )),
+        }
+    }
+
+    pub fn config(&self) -> Config {
+        Config {
+            clkout_en: self.clkout_en,
+            temp_en: self.temp_en,
+            adc_freq: self.adc_freq().unwrap_or_default(),
+            bw: self.bw,
+            ..Default::default()
+        }
+    }
+
+    pub fn calibration(&self) -> Calibration {
+        let values = &self.calibration;
+        Calibration {
+            offset: CalibrationOffset {
+                current: values.offset_current,
+                voltage: values.offset_voltage,
+                aux: values.offset_aux,
+            },
+            gain: CalibrationGain {
+                current: values.gain_current,
+                voltage: values.gain_voltage,
+                aux: values.gain_aux,
+            },
+            ..Default::default()
+        }
+    }
+}
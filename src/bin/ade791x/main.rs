@@ -0,0 +1,424 @@
+//! Command-line tool to bring up boards with ADE7912/ADE7913 ADCs on Linux, through spidev and
+//! sysfs GPIOs. The board is described in a TOML file (see the `board` module) and the same
+//! commands can run against simulated devices with `--sim`, to try the tool without hardware.
+
+mod board;
+mod sim;
+
+use std::fmt::Debug;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ade791x::*;
+use board::Board;
+use clap::{Parser, Subcommand, ValueEnum};
+use embedded_hal::blocking::spi::Transfer;
+use embedded_hal::digital::v2::OutputPin;
+use linux_embedded_hal::spidev::{SpiModeFlags, SpidevOptions};
+use linux_embedded_hal::sysfs_gpio::Direction;
+use linux_embedded_hal::{Delay, Spidev, SysfsPin};
+
+type Result<T> = std::result::Result<T, String>;
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Path of the TOML board description.
+    #[arg(short, long)]
+    board: PathBuf,
+    /// Runs the command against simulated devices instead of the hardware.
+    #[arg(long)]
+    sim: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Initializes the ADCs and prints the resulting topology and EMI settings.
+    Init,
+    /// Initializes the ADCs, dumps their registers and compares them with the expected content.
+    Dump,
+    /// Initializes the ADCs and streams the converted measurements to stdout.
+    Stream {
+        /// Output format.
+        #[arg(short, long, value_enum, default_value_t = Format::Csv)]
+        format: Format,
+        /// Number of samples to stream.
+        #[arg(short = 'n', long, default_value_t = 8000)]
+        count: usize,
+    },
+    /// Runs a calibration step and prints the resulting values in the board description format.
+    Calibrate {
+        #[command(subcommand)]
+        step: CalibrationStep,
+    },
+    /// Adjusts the synchronization of the ADCs periodically and prints the observed drift.
+    Sync {
+        /// Number of adjustments.
+        #[arg(short = 'n', long, default_value_t = 10)]
+        count: usize,
+        /// Interval between the adjustments in ms.
+        #[arg(short, long, default_value_t = 1000)]
+        interval_ms: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum CalibrationStep {
+    /// Computes the offsets as the average of the readings, with no load applied.
+    Offset {
+        /// Number of samples to average.
+        #[arg(short = 'n', long, default_value_t = 8000)]
+        samples: usize,
+    },
+    /// Computes the gains as the ratio between the known RMS load and the RMS of the readings,
+    /// with the offsets of the board description applied.
+    Gain {
+        /// Number of samples, spanning an integer number of line cycles.
+        #[arg(short = 'n', long, default_value_t = 8000)]
+        samples: usize,
+        /// RMS current applied, in A.
+        #[arg(long)]
+        current: f32,
+        /// RMS voltage applied, in V.
+        #[arg(long)]
+        voltage: f32,
+        /// RMS voltage applied to the second voltage channel, in V.
+        #[arg(long)]
+        aux: Option<f32>,
+    },
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum Format {
+    Csv,
+    Json,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = Board::load(&cli.board).and_then(|board| match board.devices.len() {
+        1 => run::<1>(&cli, &board),
+        2 => run::<2>(&cli, &board),
+        3 => run::<3>(&cli, &board),
+        4 => run::<4>(&cli, &board),
+        n => Err(format!(
+            "unsupported number of devices: {n}, expected 1 to 4"
+        )),
+    });
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Opens the hardware, or the simulated devices, described by the board and runs the command.
+fn run<const N: usize>(cli: &Cli, board: &Board) -> Result<()> {
+    let chips: [Chip; N] = std::array::from_fn(|i| board.devices[i].chip());
+    if cli.sim {
+        let (spi, cs) = sim::new(&chips);
+        let mut cs = cs.into_iter();
+        let adcs = chips.map(|chip| (cs.next().unwrap(), chip));
+        return execute(&cli.command, board, poly::Ade791x::new(spi, adcs));
+    }
+    let mut spi =
+        Spidev::open(&board.spi).map_err(|e| format!("cannot open {}: {e}", board.spi))?;
+    let options = SpidevOptions::new()
+        .bits_per_word(8)
+        .max_speed_hz(board.speed_hz)
+        .mode(SpiModeFlags::SPI_MODE_3)
+        .build();
+    spi.configure(&options)
+        .map_err(|e| format!("cannot configure {}: {e}", board.spi))?;
+    let mut exported = Vec::with_capacity(N);
+    let result = open_cs(board, chips, &mut exported)
+        .and_then(|adcs| execute(&cli.command, board, poly::Ade791x::new(spi, adcs)));
+    for pin in exported {
+        if let Err(e) = pin.unexport() {
+            eprintln!("warning: cannot unexport GPIO {}: {e}", pin.get_pin());
+        }
+    }
+    result
+}
+
+/// Exports and configures the sysfs GPIOs of the CS pins of the given chips. The GPIOs exported by
+/// this call are pushed to `exported`, so that they can be unexported when the command ends, while
+/// the ones that were already exported are left as found.
+fn open_cs<const N: usize>(
+    board: &Board,
+    chips: [Chip; N],
+    exported: &mut Vec<SysfsPin>,
+) -> Result<[(SysfsPin, Chip); N]> {
+    let mut adcs = Vec::with_capacity(N);
+    for (device, chip) in board.devices.iter().zip(chips) {
+        let cs = SysfsPin::new(device.cs);
+        if !cs.is_exported() {
+            cs.export()
+                .map_err(|e| format!("cannot export GPIO {}: {e}", device.cs))?;
+            exported.push(SysfsPin::new(device.cs));
+        }
+        cs.set_direction(Direction::High)
+            .map_err(|e| format!("cannot configure GPIO {}: {e}", device.cs))?;
+        adcs.push((cs, chip));
+    }
+    Ok(adcs.try_into().ok().unwrap())
+}
+
+/// Runs the command on the given ADCs.
+fn execute<SPI, CS, S, P, const N: usize>(
+    command: &Command,
+    board: &Board,
+    mut adc: poly::Ade791x<SPI, CS, N>,
+) -> Result<()>
+where
+    SPI: Transfer<u8, Error = S>,
+    CS: OutputPin<Error = P>,
+    S: Debug,
+    P: Debug,
+{
+    let calibration = std::array::from_fn(|i| board.devices[i].calibration());
+    match command {
+        Command::Init => {
+            init(&mut adc, board, calibration)?;
+            let topology = adc.topology();
+            let chips = adc.chips();
+            for (i, emi_ctrl) in adc.recommended_emi_ctrl().into_iter().enumerate() {
+                println!(
+                    "adc {i}: {:?}, clock {:?}, emi_ctrl {:#04x}{}",
+                    chips[i],
+                    topology.clock[i],
+                    u8::from(emi_ctrl),
+                    if topology.dready == i { ", dready" } else { "" }
+                );
+            }
+        }
+        Command::Dump => {
+            init(&mut adc, board, calibration)?;
+            let dump = check(adc.dump_registers())?;
+            let expected = adc.expected_registers();
+            for (i, dump) in dump.iter().enumerate() {
+                println!("adc {i}: {dump:#?}");
+                let diff = dump.diff(&expected[i]);
+                if !diff.is_empty() {
+                    println!("adc {i}: unexpected content {diff:#?}");
+                }
+            }
+        }
+        Command::Stream { format, count } => {
+            init(&mut adc, board, calibration)?;
+            stream(&mut adc, *format, *count)?;
+        }
+        Command::Calibrate { step } => calibrate(&mut adc, board, step)?,
+        Command::Sync { count, interval_ms } => {
+            init(&mut adc, board, calibration)?;
+            let mut supervisor = sync::SyncSupervisor::<N, 64>::default();
+            for k in 0..*count {
+                if k > 0 {
+                    thread::sleep(Duration::from_millis(*interval_ms));
+                }
+                let drift = check(adc.adjust_sync())?;
+                let alarms = supervisor.record(drift);
+                println!("{k}: drift {drift:?}, alarms {alarms:?}");
+            }
+            for i in 0..N {
+                println!("adc {i}: {:?}", supervisor.stats(i));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Initializes the ADCs with the configurations of the board description.
+fn init<SPI, CS, S, P, const N: usize>(
+    adc: &mut poly::Ade791x<SPI, CS, N>,
+    board: &Board,
+    calibration: [Calibration; N],
+) -> Result<()>
+where
+    SPI: Transfer<u8, Error = S>,
+    CS: OutputPin<Error = P>,
+    S: Debug,
+    P: Debug,
+{
+    let config = std::array::from_fn(|i| board.devices[i].config());
+    let emi_ctrl = adc.recommended_emi_ctrl();
+    check(adc.init(&mut Delay, config, calibration, emi_ctrl))
+}
+
+/// Reads the given number of samples, one per ADC output period. The reads are scheduled on
+/// absolute deadlines, so that the oversleeps do not accumulate, and stamped by the sample tracker
+/// of the driver: a read taken before a new output is available is skipped as a duplicate, and the
+/// samples missed because of a late wake-up are reported at the end.
+fn read<SPI, CS, S, P, const N: usize>(
+    adc: &mut poly::Ade791x<SPI, CS, N>,
+    count: usize,
+    mut f: impl FnMut(usize, [Measurement; N]) -> Result<()>,
+) -> Result<()>
+where
+    SPI: Transfer<u8, Error = S>,
+    CS: OutputPin<Error = P>,
+    S: Debug,
+    P: Debug,
+{
+    let period = Duration::from_micros(1_000_000 / adc.sample_rate() as u64);
+    let start = Instant::now();
+    let mut deadline = start;
+    let mut k = 0;
+    while k < count {
+        let now = Instant::now();
+        match deadline.checked_duration_since(now) {
+            Some(wait) => thread::sleep(wait),
+            None => deadline = now,
+        }
+        deadline += period;
+        let timestamp_us = start.elapsed().as_micros() as u64;
+        let tracked = check(adc.get_tracked_measurement(timestamp_us))?;
+        if tracked.duplicate {
+            continue;
+        }
+        f(k, tracked.value)?;
+        k += 1;
+    }
+    let tracker = adc.tracker();
+    if tracker.missed() > 0 || tracker.duplicates() > 0 {
+        eprintln!(
+            "warning: {} samples missed, {} duplicate reads skipped",
+            tracker.missed(),
+            tracker.duplicates()
+        );
+    }
+    Ok(())
+}
+
+fn stream<SPI, CS, S, P, const N: usize>(
+    adc: &mut poly::Ade791x<SPI, CS, N>,
+    format: Format,
+    count: usize,
+) -> Result<()>
+where
+    SPI: Transfer<u8, Error = S>,
+    CS: OutputPin<Error = P>,
+    S: Debug,
+    P: Debug,
+{
+    let mut out = std::io::stdout().lock();
+    if let Format::Csv = format {
+        let mut header = String::from("index");
+        for i in 0..N {
+            header.push_str(&format!(",current{i},voltage{i},aux{i}"));
+        }
+        writeln!(out, "{header}").map_err(|e| e.to_string())?;
+    }
+    read(adc, count, |k, measurement| {
+        let line = match format {
+            Format::Csv => {
+                let mut line = k.to_string();
+                for m in measurement {
+                    line.push_str(&format!(",{},{},{}", m.current, m.voltage, aux(&m.aux)));
+                }
+                line
+            }
+            Format::Json => serde_json::json!({
+                "index": k,
+                "adc": measurement.iter().map(|m| serde_json::json!({
+                    "current": m.current,
+                    "voltage": m.voltage,
+                    "aux": aux(&m.aux),
+                    "aux_temperature": matches!(m.aux, MeasurementAux::Temperature(_)),
+                })).collect::<Vec<_>>(),
+            })
+            .to_string(),
+        };
+        writeln!(out, "{line}").map_err(|e| e.to_string())
+    })
+}
+
+fn calibrate<SPI, CS, S, P, const N: usize>(
+    adc: &mut poly::Ade791x<SPI, CS, N>,
+    board: &Board,
+    step: &CalibrationStep,
+) -> Result<()>
+where
+    SPI: Transfer<u8, Error = S>,
+    CS: OutputPin<Error = P>,
+    S: Debug,
+    P: Debug,
+{
+    match step {
+        CalibrationStep::Offset { samples } => {
+            init(adc, board, [Calibration::default(); N])?;
+            let mut sum = [[0.0_f64; 3]; N];
+            read(adc, *samples, |_, measurement| {
+                for (sum, m) in sum.iter_mut().zip(measurement) {
+                    sum[0] += m.current as f64;
+                    sum[1] += m.voltage as f64;
+                    sum[2] += aux(&m.aux) as f64;
+                }
+                Ok(())
+            })?;
+            for (i, sum) in sum.iter().enumerate() {
+                let mean = sum.map(|x| x / (*samples).max(1) as f64);
+                println!("[device.calibration] # adc {i}");
+                println!("offset_current = {}", mean[0] as f32);
+                println!("offset_voltage = {}", mean[1] as f32);
+                if !adc.conversion()[i].aux_temperature {
+                    println!("offset_aux = {}", mean[2] as f32);
+                }
+            }
+        }
+        CalibrationStep::Gain {
+            samples,
+            current,
+            voltage,
+            aux: aux_voltage,
+        } => {
+            let calibration = std::array::from_fn(|i| {
+                let mut calibration = board.devices[i].calibration();
+                calibration.gain = CalibrationGain::default();
+                calibration
+            });
+            init(adc, board, calibration)?;
+            let mut sum = [[0.0_f64; 3]; N];
+            read(adc, *samples, |_, measurement| {
+                for (sum, m) in sum.iter_mut().zip(measurement) {
+                    sum[0] += (m.current as f64).powi(2);
+                    sum[1] += (m.voltage as f64).powi(2);
+                    sum[2] += (aux(&m.aux) as f64).powi(2);
+                }
+                Ok(())
+            })?;
+            for (i, sum) in sum.iter().enumerate() {
+                let rms = sum.map(|x| (x / (*samples).max(1) as f64).sqrt());
+                println!("[device.calibration] # adc {i}");
+                println!("gain_current = {}", (*current as f64 / rms[0]) as f32);
+                println!("gain_voltage = {}", (*voltage as f64 / rms[1]) as f32);
+                if let (Some(aux_voltage), false) =
+                    (aux_voltage, adc.conversion()[i].aux_temperature)
+                {
+                    println!("gain_aux = {}", (*aux_voltage as f64 / rms[2]) as f32);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the value of the auxiliary channel, or NaN if it is unavailable.
+fn aux(aux: &MeasurementAux) -> f32 {
+    match aux {
+        MeasurementAux::Voltage(x) | MeasurementAux::Temperature(x) => *x,
+        _ => f32::NAN,
+    }
+}
+
+/// Maps a driver error to a printable message.
+fn check<T, S: Debug, P: Debug>(result: std::result::Result<T, Error<S, P>>) -> Result<T> {
+    result.map_err(|e| e.to_string())
+}
@@ -0,0 +1,213 @@
+//! In-process stand-in for ADE7912/ADE7913 devices sharing an SPI bus, used to run the tool with
+//! no hardware attached. The simulated devices answer register reads and writes, honour the LOCK
+//! register and the broadcast of commands to the selected devices, and produce 50 Hz waveforms
+//! shifted by 120° between consecutive devices.
+
+use std::cell::RefCell;
+use std::convert::Infallible;
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+use ade791x::Chip;
+use embedded_hal::blocking::spi;
+use embedded_hal::digital::v2::OutputPin;
+
+const IWV_AMPLITUDE: f64 = 2_000_000.0;
+const VWV_AMPLITUDE: f64 = 3_000_000.0;
+/// V2WV value corresponding to about 25 °C with the datasheet calibration.
+const TEMPERATURE_CODE: i32 = 3_800_000;
+const LINE_FREQUENCY: f64 = 50.0;
+
+/// Creates the simulated devices, returning the SPI bus and the CS pins.
+pub fn new(chips: &[Chip]) -> (SimSpi, Vec<SimCs>) {
+    let bus = Rc::new(RefCell::new(
+        chips
+            .iter()
+            .enumerate()
+            .map(|(i, chip)| Device::new(i, *chip))
+            .collect::<Vec<_>>(),
+    ));
+    let cs = (0..chips.len()).map(|i| SimCs(bus.clone(), i)).collect();
+    (SimSpi(bus), cs)
+}
+
+/// SPI bus of the simulated devices.
+pub struct SimSpi(Rc<RefCell<Vec<Device>>>);
+
+/// CS pin of a simulated device.
+pub struct SimCs(Rc<RefCell<Vec<Device>>>, usize);
+
+struct Device {
+    index: usize,
+    chip: Chip,
+    selected: bool,
+    config: u8,
+    emi_ctrl: u8,
+    locked: bool,
+    tempos: i8,
+    counter: u16,
+    cnt_snapshot: u16,
+    sample: u64,
+}
+
+impl Device {
+    fn new(index: usize, chip: Chip) -> Self {
+        Self {
+            index,
+            chip,
+            selected: false,
+            config: 0x00,
+            emi_ctrl: 0xFF,
+            locked: false,
+            tempos: 3,
+            counter: 0,
+            cnt_snapshot: 0,
+            sample: 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.index, self.chip);
+    }
+
+    fn status0(&self) -> u8 {
+        (self.locked as u8) << 2
+    }
+
+    /// Returns the content of the burst registers, from IWV to CNT_SNAPSHOT.
+    fn burst(&self) -> [u8; 14] {
+        let adc_freq = [8000.0, 4000.0, 2000.0, 1000.0][((self.config >> 4) & 0x03) as usize];
+        let phase = 2.0 * PI * LINE_FREQUENCY * self.sample as f64 / adc_freq
+            - 2.0 * PI * self.index as f64 / 3.0;
+        let iwv = (IWV_AMPLITUDE * (phase - PI / 6.0).sin()) as i32;
+        let v1wv = (VWV_AMPLITUDE * phase.sin()) as i32;
+        let v2wv = if self.chip == Chip::ADE7912 || self.config & 0x08 != 0 {
+            TEMPERATURE_CODE
+        } else {
+            (VWV_AMPLITUDE * 0.5 * phase.sin()) as i32
+        };
+        let mut burst = [0; 14];
+        burst[0..3].copy_from_slice(&iwv.to_be_bytes()[1..]);
+        burst[3..6].copy_from_slice(&v1wv.to_be_bytes()[1..]);
+        burst[6..9].copy_from_slice(&v2wv.to_be_bytes()[1..]);
+        let adc_crc = crc(&burst[..9]);
+        burst[9..11].copy_from_slice(&adc_crc.to_be_bytes());
+        burst[11] = self.status0();
+        burst[12..14].copy_from_slice(&self.cnt_snapshot.to_be_bytes());
+        burst
+    }
+
+    fn read(&mut self, addr: u8, data: &mut [u8]) {
+        let start = match addr {
+            0x00 => Some(0),
+            0x01 => Some(3),
+            0x02 => Some(6),
+            0x04 => Some(9),
+            0x09 => Some(11),
+            0x07 => Some(12),
+            _ => None,
+        };
+        if let Some(start) = start {
+            let burst = self.burst();
+            for (byte, value) in data.iter_mut().zip(burst[start..].iter()) {
+                *byte = *value;
+            }
+            if addr == 0x00 {
+                self.sample += 1;
+            }
+            return;
+        }
+        let value = match addr {
+            0x05 => crc(&[self.config, self.emi_ctrl]).to_be_bytes().to_vec(),
+            0x08 => vec![self.config],
+            0x0C => vec![self.counter as u8],
+            0x0D => vec![(self.counter >> 8) as u8],
+            0x0E => vec![self.emi_ctrl],
+            0x18 => vec![self.tempos as u8],
+            _ => vec![0],
+        };
+        for (byte, value) in data.iter_mut().zip(value) {
+            *byte = value;
+        }
+    }
+
+    fn write(&mut self, addr: u8, value: u8) {
+        if addr == 0x0A {
+            match value {
+                0x9C => self.locked = false,
+                0xCA => self.locked = true,
+                _ => {}
+            }
+            return;
+        }
+        if self.locked {
+            return;
+        }
+        match addr {
+            0x08 if value & 0x40 != 0 => self.reset(),
+            0x08 => self.config = value,
+            0x0B if value & 0x02 != 0 => self.cnt_snapshot = self.counter,
+            0x0C => self.counter = (self.counter & 0xFF00) | value as u16,
+            0x0D => self.counter = (self.counter & 0x00FF) | (value as u16) << 8,
+            0x0E => self.emi_ctrl = value,
+            _ => {}
+        }
+    }
+}
+
+impl spi::Transfer<u8> for SimSpi {
+    type Error = Infallible;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+        let mut devices = self.0.borrow_mut();
+        if words.len() >= 8 && words.iter().all(|w| *w == 0) {
+            devices
+                .iter_mut()
+                .filter(|d| d.selected)
+                .for_each(Device::reset);
+            return Ok(words);
+        }
+        let addr = words[0] >> 3;
+        if words[0] & 0x04 != 0 {
+            if let Some(device) = devices.iter_mut().find(|d| d.selected) {
+                device.read(addr, &mut words[1..]);
+            }
+        } else if let Some(value) = words.get(1) {
+            devices
+                .iter_mut()
+                .filter(|d| d.selected)
+                .for_each(|d| d.write(addr, *value));
+        }
+        Ok(words)
+    }
+}
+
+impl OutputPin for SimCs {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.borrow_mut()[self.1].selected = true;
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.borrow_mut()[self.1].selected = false;
+        Ok(())
+    }
+}
+
+/// Returns the CRC-16-CCITT of the given bytes.
+fn crc(bytes: &[u8]) -> u16 {
+    let mut crc = 0xFFFF_u16;
+    for byte in bytes {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
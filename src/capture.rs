@@ -0,0 +1,266 @@
+//! Capture of the waveforms of a polyphase metering system around an event.
+//!
+//! A [`Capture`] is fed with the sample stream of the ADCs, either with
+//! [`poly::Ade791x::capture()`] or with [`Capture::push()`], keeping the last samples in a
+//! fixed-size buffer. When the [`Trigger`] condition is met, or when [`Capture::trigger()`] is
+//! called, the capture records the configured number of post-trigger samples and then freezes the
+//! buffer, so that it can be read with [`Capture::frames()`] or [`Capture::measurements()`].
+//!
+//! ```rust ignore
+//! let config = capture::CaptureConfig {
+//!     pre: 160,
+//!     post: 480,
+//!     trigger: capture::Trigger::CurrentAbove(20.0),
+//!     channels: [Channels::IwvV1wv; 3],
+//! };
+//! let mut capture = capture::Capture::<3, 640>::new(config, adc.conversion());
+//! // DREADY ISR
+//! if adc.capture(&mut capture).unwrap() == capture::CaptureState::Frozen {
+//!     // Read the captured frames
+//! }
+//! ```
+
+use super::*;
+
+/// Represents the conditions that start the recording of the post-trigger samples.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Trigger {
+    /// The capture is only triggered by [`Capture::trigger()`].
+    Manual,
+    /// The capture is triggered when the absolute value of the current of any ADC is above the
+    /// given threshold in Amperes.
+    CurrentAbove(f32),
+    /// The capture is triggered when the peak of the absolute value of the voltage of any ADC over a
+    /// window of samples is below the given threshold in Volts. The window should span at least
+    /// one half cycle of the mains voltage, e.g. 80 samples at 8 kHz for a 50 Hz system.
+    VoltageDip {
+        /// Voltage threshold in Volts.
+        threshold: f32,
+        /// Length of the window in samples.
+        window: u16,
+    },
+}
+
+/// Contains the configuration of a [`Capture`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CaptureConfig<const N: usize> {
+    /// Number of samples recorded before the trigger.
+    pub pre: usize,
+    /// Number of samples recorded from the trigger on, including the triggering sample.
+    pub post: usize,
+    /// Condition that triggers the capture as a [`Trigger`].
+    pub trigger: Trigger,
+    /// Channels recorded for each ADC as a [`Channels`] value. The channels that are not recorded
+    /// are set to zero in the frames, flagged as unavailable in the measurements and are not
+    /// evaluated by the trigger.
+    pub channels: [Channels; N],
+}
+
+/// Represents the possible states of a [`Capture`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CaptureState {
+    /// The capture is recording the pre-trigger samples, waiting for the trigger.
+    Armed,
+    /// The capture has been triggered and is recording the post-trigger samples.
+    Triggered,
+    /// The capture is complete and the buffer is frozen until [`Capture::rearm()`] is called.
+    Frozen,
+}
+
+/// Captures the waveforms of a polyphase metering system composed of `N` ADCs in a buffer of `L`
+/// samples, without allocation.
+pub struct Capture<const N: usize, const L: usize> {
+    config: CaptureConfig<N>,
+    conversion: [Conversion; N],
+    buffer: [[RawMeasurement; N]; L],
+    next: usize,
+    len: usize,
+    state: CaptureState,
+    remaining: usize,
+    trigger_position: usize,
+    window_peak: [f32; N],
+    window_len: u16,
+}
+
+impl<const N: usize, const L: usize> Capture<N, L> {
+    /// Creates a new armed [`Capture`], given its configuration and the [`Conversion`] structs used
+    /// to evaluate the trigger. The pre-trigger length is limited to `L` samples and the
+    /// post-trigger length to the remaining part of the buffer.
+    /// # Arguments
+    /// * `config` - The [`CaptureConfig`] struct containing the configuration.
+    /// * `conversion` - An array of [`Conversion`] structs, as returned by
+    ///   [`poly::Ade791x::conversion()`].
+    pub fn new(mut config: CaptureConfig<N>, conversion: [Conversion; N]) -> Self {
+        config.pre = config.pre.min(L);
+        config.post = config.post.min(L - config.pre);
+        Self {
+            config,
+            conversion,
+            buffer: [[RawMeasurement {
+                iwv: 0,
+                v1wv: 0,
+                v2wv: 0,
+            }; N]; L],
+            next: 0,
+            len: 0,
+            state: CaptureState::Armed,
+            remaining: 0,
+            trigger_position: 0,
+            window_peak: [0.0; N],
+            window_len: 0,
+        }
+    }
+
+    /// Discards the recorded samples and arms the capture again.
+    pub fn rearm(&mut self) {
+        self.next = 0;
+        self.len = 0;
+        self.state = CaptureState::Armed;
+        self.window_peak = [0.0; N];
+        self.window_len = 0;
+    }
+
+    /// Returns the state of the capture as a [`CaptureState`].
+    pub fn state(&self) -> CaptureState {
+        self.state
+    }
+
+    /// Triggers the capture, if armed. The next pushed sample is the triggering sample.
+    pub fn trigger(&mut self) {
+        if self.state == CaptureState::Armed {
+            self.start();
+        }
+    }
+
+    /// Records a sample, evaluating the trigger if the capture is armed. Samples pushed while the
+    /// capture is frozen are ignored. This method returns the state of the capture after the
+    /// sample is recorded.
+    /// # Arguments
+    /// * `raw_measurement` - An array of [`RawMeasurement`] structs containing the sample.
+    pub fn push(&mut self, raw_measurement: &[RawMeasurement; N]) -> CaptureState {
+        if self.state == CaptureState::Frozen {
+            return self.state;
+        }
+        let frame: [RawMeasurement; N] =
+            core::array::from_fn(|i| mask(self.config.channels[i], raw_measurement[i]));
+        if self.state == CaptureState::Armed && self.is_triggered(&frame) {
+            self.start();
+            if self.state == CaptureState::Frozen {
+                return self.state;
+            }
+        }
+        if L > 0 {
+            self.buffer[self.next] = frame;
+            self.next = (self.next + 1) % L;
+        }
+        match self.state {
+            CaptureState::Armed => self.len = (self.len + 1).min(self.config.pre),
+            _ => {
+                self.len += 1;
+                self.remaining = self.remaining.saturating_sub(1);
+                if self.remaining == 0 {
+                    self.state = CaptureState::Frozen;
+                }
+            }
+        }
+        self.state
+    }
+
+    /// Returns the recorded frames from the oldest to the newest, each as an array of
+    /// [`RawMeasurement`] structs.
+    pub fn frames(&self) -> impl Iterator<Item = &[RawMeasurement; N]> {
+        let start = (self.next + L - self.len) % L.max(1);
+        (0..self.len).map(move |k| &self.buffer[(start + k) % L])
+    }
+
+    /// Returns the recorded frames from the oldest to the newest, each converted to an array of
+    /// [`Measurement`] structs.
+    pub fn measurements(&self) -> impl Iterator<Item = [Measurement; N]> + '_ {
+        self.frames().map(|frame| {
+            core::array::from_fn(|i| {
+                let mut measurement = self.conversion[i].convert(&frame[i]);
+                self.config.channels[i].mask(&mut measurement);
+                measurement
+            })
+        })
+    }
+
+    /// Returns the position of the triggering sample in [`Self::frames()`], once triggered.
+    pub fn trigger_position(&self) -> Option<usize> {
+        match self.state {
+            CaptureState::Armed => None,
+            _ => Some(self.trigger_position),
+        }
+    }
+
+    /// Returns the [`CaptureConfig`] struct of the capture, with the lengths limited to the buffer.
+    pub fn config(&self) -> &CaptureConfig<N> {
+        &self.config
+    }
+
+    /// Returns the [`Conversion`] structs of the capture.
+    pub fn conversion(&self) -> &[Conversion; N] {
+        &self.conversion
+    }
+
+    /// Starts recording the post-trigger samples.
+    fn start(&mut self) {
+        self.trigger_position = self.len;
+        self.remaining = self.config.post;
+        self.state = if self.remaining == 0 {
+            CaptureState::Frozen
+        } else {
+            CaptureState::Triggered
+        };
+    }
+
+    /// Evaluates the trigger condition on the given frame.
+    /// # Arguments
+    /// * `frame` - An array of [`RawMeasurement`] structs containing the masked sample.
+    fn is_triggered(&mut self, frame: &[RawMeasurement; N]) -> bool {
+        match self.config.trigger {
+            Trigger::Manual => false,
+            Trigger::CurrentAbove(threshold) => {
+                (0..N).any(|i| self.conversion[i].current.apply(frame[i].iwv).abs() > threshold)
+            }
+            Trigger::VoltageDip { threshold, window } => {
+                for (i, val) in frame.iter().enumerate() {
+                    let voltage = self.conversion[i].voltage.apply(val.v1wv).abs();
+                    self.window_peak[i] = self.window_peak[i].max(voltage);
+                }
+                self.window_len += 1;
+                if self.window_len < window {
+                    return false;
+                }
+                let dip = (0..N).any(|i| {
+                    self.config.channels[i] != Channels::Iwv && self.window_peak[i] < threshold
+                });
+                self.window_peak = [0.0; N];
+                self.window_len = 0;
+                dip
+            }
+        }
+    }
+}
+
+/// Sets to zero the fields of a [`RawMeasurement`] struct that correspond to the channels that are
+/// not selected.
+/// # Arguments
+/// * `channels` - The selected channels as a [`Channels`] value.
+/// * `raw_measurement` - The [`RawMeasurement`] struct to mask.
+fn mask(channels: Channels, mut raw_measurement: RawMeasurement) -> RawMeasurement {
+    match channels {
+        Channels::Iwv => {
+            raw_measurement.v1wv = 0;
+            raw_measurement.v2wv = 0;
+        }
+        Channels::IwvV1wv => raw_measurement.v2wv = 0,
+        Channels::All | Channels::AllCrc => {}
+    }
+    raw_measurement
+}
@@ -0,0 +1,299 @@
+//! Internal binary codec shared by the serialized formats of the crate. Multi-byte values are
+//! stored in little-endian order.
+
+use super::*;
+
+/// Represents the possible errors of the codec.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum CodecError {
+    /// The buffer is too small for the value to write, or ends before the value to read.
+    BufferTooSmall,
+    /// The read value is not valid.
+    InvalidData,
+}
+
+/// Returns the CRC-16-CCITT (polynomial 0x1021, initial value 0xFFFF) of the given bytes.
+/// # Arguments
+/// * `bytes` - The bytes to compute the CRC of.
+pub(crate) fn crc16(bytes: impl IntoIterator<Item = u8>) -> u16 {
+    let mut crc = 0xFFFF_u16;
+    for byte in bytes {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Writes values into a byte buffer.
+pub(crate) struct Writer<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> Writer<'a> {
+    /// Creates a new [`Writer`] at the beginning of the given buffer.
+    /// # Arguments
+    /// * `buf` - The buffer to write into.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    /// Returns the number of bytes written.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Writes the given bytes.
+    /// # Arguments
+    /// * `bytes` - The bytes to write.
+    pub fn bytes(&mut self, bytes: &[u8]) -> Result<(), CodecError> {
+        let end = self.pos + bytes.len();
+        self.buf
+            .get_mut(self.pos..end)
+            .ok_or(CodecError::BufferTooSmall)?
+            .copy_from_slice(bytes);
+        self.pos = end;
+        Ok(())
+    }
+
+    pub fn u8(&mut self, x: u8) -> Result<(), CodecError> {
+        self.bytes(&[x])
+    }
+
+    pub fn u16(&mut self, x: u16) -> Result<(), CodecError> {
+        self.bytes(&x.to_le_bytes())
+    }
+
+    /// Writes the 24 least significant bits of the given value.
+    pub fn i24(&mut self, x: i32) -> Result<(), CodecError> {
+        self.bytes(&x.to_le_bytes()[..3])
+    }
+
+    pub fn f32(&mut self, x: f32) -> Result<(), CodecError> {
+        self.bytes(&x.to_le_bytes())
+    }
+
+    /// Writes the given value as a zigzag-encoded LEB128 varint, taking 1 byte for values between
+    /// -64 and 63 and up to 5 bytes.
+    pub fn varint(&mut self, x: i32) -> Result<(), CodecError> {
+        let mut x = ((x << 1) ^ (x >> 31)) as u32;
+        while x >= 0x80 {
+            self.u8((x as u8) | 0x80)?;
+            x >>= 7;
+        }
+        self.u8(x as u8)
+    }
+
+    pub fn option_f32(&mut self, x: Option<f32>) -> Result<(), CodecError> {
+        match x {
+            Some(x) => {
+                self.u8(1)?;
+                self.f32(x)
+            }
+            None => self.u8(0),
+        }
+    }
+
+    pub fn chip(&mut self, chip: Chip) -> Result<(), CodecError> {
+        self.u8(match chip {
+            Chip::ADE7912 => 0,
+            Chip::ADE7913 => 1,
+        })
+    }
+
+    pub fn channels(&mut self, channels: Channels) -> Result<(), CodecError> {
+        self.u8(match channels {
+            Channels::Iwv => 0,
+            Channels::IwvV1wv => 1,
+            Channels::All => 2,
+            Channels::AllCrc => 3,
+        })
+    }
+
+    pub fn tempco(&mut self, tempco: &TempCo) -> Result<(), CodecError> {
+        match tempco {
+            TempCo::None => self.u8(0),
+            TempCo::Linear { t0, k } => {
+                self.u8(1)?;
+                self.f32(*t0)?;
+                self.f32(*k)
+            }
+            TempCo::Table(table) => {
+                self.u8(2)?;
+                for (temperature, factor) in table {
+                    self.f32(*temperature)?;
+                    self.f32(*factor)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn calibration(&mut self, calibration: &Calibration) -> Result<(), CodecError> {
+        self.f32(calibration.offset.current)?;
+        self.f32(calibration.offset.voltage)?;
+        self.option_f32(calibration.offset.aux)?;
+        self.f32(calibration.gain.current)?;
+        self.f32(calibration.gain.voltage)?;
+        self.option_f32(calibration.gain.aux)?;
+        match &calibration.temperature {
+            Some(temperature) => {
+                self.u8(1)?;
+                self.f32(temperature.gain)?;
+                self.f32(temperature.offset)?;
+                self.u8(temperature.tempos as u8)?;
+            }
+            None => self.u8(0)?,
+        }
+        self.tempco(&calibration.tempco.current)?;
+        self.tempco(&calibration.tempco.voltage)
+    }
+}
+
+/// Reads values from a byte buffer.
+pub(crate) struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Creates a new [`Reader`] at the beginning of the given buffer.
+    /// # Arguments
+    /// * `buf` - The buffer to read from.
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    /// Returns `true` if all the bytes have been read, `false` otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.buf.len()
+    }
+
+    /// Reads the given number of bytes.
+    /// # Arguments
+    /// * `len` - The number of bytes to read.
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], CodecError> {
+        let bytes = self
+            .buf
+            .get(self.pos..self.pos + len)
+            .ok_or(CodecError::BufferTooSmall)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    /// Reads a fixed-size array of bytes.
+    fn array<const L: usize>(&mut self) -> Result<[u8; L], CodecError> {
+        let mut array = [0; L];
+        array.copy_from_slice(self.bytes(L)?);
+        Ok(array)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, CodecError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, CodecError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    /// Reads a 24-bit value, extending its sign.
+    pub fn i24(&mut self) -> Result<i32, CodecError> {
+        let [b0, b1, b2] = self.array()?;
+        Ok(i32::from_le_bytes([0, b0, b1, b2]) >> 8)
+    }
+
+    pub fn f32(&mut self) -> Result<f32, CodecError> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+
+    /// Reads a zigzag-encoded LEB128 varint.
+    pub fn varint(&mut self) -> Result<i32, CodecError> {
+        let mut x = 0_u32;
+        for shift in (0..35).step_by(7) {
+            let byte = self.u8()?;
+            x |= ((byte & 0x7F) as u32) << shift;
+            if byte & 0x80 == 0 {
+                return Ok((x >> 1) as i32 ^ -((x & 1) as i32));
+            }
+        }
+        Err(CodecError::InvalidData)
+    }
+
+    pub fn option_f32(&mut self) -> Result<Option<f32>, CodecError> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(self.f32()?)),
+            _ => Err(CodecError::InvalidData),
+        }
+    }
+
+    pub fn chip(&mut self) -> Result<Chip, CodecError> {
+        match self.u8()? {
+            0 => Ok(Chip::ADE7912),
+            1 => Ok(Chip::ADE7913),
+            _ => Err(CodecError::InvalidData),
+        }
+    }
+
+    pub fn channels(&mut self) -> Result<Channels, CodecError> {
+        match self.u8()? {
+            0 => Ok(Channels::Iwv),
+            1 => Ok(Channels::IwvV1wv),
+            2 => Ok(Channels::All),
+            3 => Ok(Channels::AllCrc),
+            _ => Err(CodecError::InvalidData),
+        }
+    }
+
+    pub fn tempco(&mut self) -> Result<TempCo, CodecError> {
+        match self.u8()? {
+            0 => Ok(TempCo::None),
+            1 => Ok(TempCo::Linear {
+                t0: self.f32()?,
+                k: self.f32()?,
+            }),
+            2 => {
+                let mut table = [(0.0, 0.0); TEMPCO_TABLE_LEN];
+                for point in &mut table {
+                    *point = (self.f32()?, self.f32()?);
+                }
+                Ok(TempCo::Table(table))
+            }
+            _ => Err(CodecError::InvalidData),
+        }
+    }
+
+    pub fn calibration(&mut self) -> Result<Calibration, CodecError> {
+        Ok(Calibration {
+            offset: CalibrationOffset {
+                current: self.f32()?,
+                voltage: self.f32()?,
+                aux: self.option_f32()?,
+            },
+            gain: CalibrationGain {
+                current: self.f32()?,
+                voltage: self.f32()?,
+                aux: self.option_f32()?,
+            },
+            temperature: match self.u8()? {
+                0 => None,
+                1 => Some(TemperatureCalibration {
+                    gain: self.f32()?,
+                    offset: self.f32()?,
+                    tempos: self.u8()? as i8,
+                }),
+                _ => return Err(CodecError::InvalidData),
+            },
+            tempco: CalibrationTempCo {
+                current: self.tempco()?,
+                voltage: self.tempco()?,
+            },
+        })
+    }
+}
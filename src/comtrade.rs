@@ -0,0 +1,266 @@
+//! Export of captured waveforms in the IEEE C37.111-2013 COMTRADE format.
+//!
+//! The exporter writes the configuration (`.cfg`) and data (`.dat`) files of a frozen
+//! [`capture::Capture`], with one analog channel per recorded channel of each ADC. The raw ADC
+//! values are stored unchanged, and the `a` and `b` scaling factors of each channel are taken from
+//! the [`Conversion`] of the capture, so that `a * x + b` gives the same metrics as
+//! [`poly::Ade791x::get_measurement()`], except for the tempco compensation. The data file can be
+//! written in the ASCII or in the 32-bit binary variant, as the 24-bit ADC values do not fit in the
+//! 16-bit binary variant. This module requires the `std` feature.
+//!
+//! ```rust ignore
+//! let header = comtrade::Header {
+//!     station_name: "Substation",
+//!     device_id: "Meter 1",
+//!     line_frequency: 50.0,
+//!     adc_freq: config[0].adc_freq,
+//!     trigger_time_us: 1_700_000_000_000_000,
+//! };
+//! comtrade::export("capture", &header, &capture, comtrade::Format::Binary32).unwrap();
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use super::*;
+
+/// Minimum raw value of the ADC.
+const RAW_MIN: i32 = -8_388_608;
+/// Maximum raw value of the ADC.
+const RAW_MAX: i32 = 8_388_607;
+
+/// Represents the possible variants of the data file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    /// The data file is a text file with comma-separated values.
+    Ascii,
+    /// The data file is a binary file with 32-bit little-endian values.
+    Binary32,
+}
+
+/// Contains the information written in the configuration file alongside the channels.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Header<'a> {
+    /// Name of the station.
+    pub station_name: &'a str,
+    /// Identification of the recording device.
+    pub device_id: &'a str,
+    /// Nominal frequency of the mains in Hz.
+    pub line_frequency: f32,
+    /// ADC output frequency of the ADCs, as set in [`Config`].
+    pub adc_freq: AdcFreqVal,
+    /// Time of the triggering sample in µs since the Unix epoch, in UTC.
+    pub trigger_time_us: i64,
+}
+
+/// Writes the configuration and data files of the given capture, adding the `.cfg` and `.dat`
+/// extensions to the given path.
+/// # Arguments
+/// * `path` - The path of the files, without extension.
+/// * `header` - The [`Header`] struct containing the information on the recording.
+/// * `capture` - The [`capture::Capture`] containing the recorded frames.
+/// * `format` - The variant of the data file as a [`Format`].
+pub fn export<const N: usize, const L: usize>(
+    path: impl AsRef<Path>,
+    header: &Header,
+    capture: &capture::Capture<N, L>,
+    format: Format,
+) -> io::Result<()> {
+    let path = path.as_ref();
+    let mut cfg = BufWriter::new(File::create(path.with_extension("cfg"))?);
+    write_cfg(&mut cfg, header, capture, format)?;
+    cfg.flush()?;
+    let mut dat = BufWriter::new(File::create(path.with_extension("dat"))?);
+    write_dat(&mut dat, header, capture, format)?;
+    dat.flush()
+}
+
+/// Writes the configuration file of the given capture.
+/// # Arguments
+/// * `writer` - The destination implementing the [`Write`] trait.
+/// * `header` - The [`Header`] struct containing the information on the recording.
+/// * `capture` - The [`capture::Capture`] containing the recorded frames.
+/// * `format` - The variant of the data file as a [`Format`].
+pub fn write_cfg<const N: usize, const L: usize>(
+    writer: &mut impl Write,
+    header: &Header,
+    capture: &capture::Capture<N, L>,
+    format: Format,
+) -> io::Result<()> {
+    let channels = channels(capture);
+    write!(
+        writer,
+        "{},{},2013\r\n",
+        header.station_name, header.device_id
+    )?;
+    write!(writer, "{},{}A,0D\r\n", channels.len(), channels.len())?;
+    for (k, channel) in channels.iter().enumerate() {
+        write!(
+            writer,
+            "{},{}{},{},,{},{:e},{:e},0,{},{},1,1,P\r\n",
+            k + 1,
+            channel.name,
+            channel.phase,
+            channel.phase,
+            channel.unit,
+            channel.coefficients.scale,
+            channel.coefficients.offset,
+            RAW_MIN,
+            RAW_MAX,
+        )?;
+    }
+    write!(writer, "{}\r\n", header.line_frequency)?;
+    write!(writer, "1\r\n")?;
+    let frames = capture.frames().count();
+    write!(writer, "{},{}\r\n", header.adc_freq.hz(), frames)?;
+    let period_us = 1_000_000 / header.adc_freq.hz() as i64;
+    let pre_trigger = capture.trigger_position().unwrap_or(0) as i64;
+    write_time(writer, header.trigger_time_us - pre_trigger * period_us)?;
+    write_time(writer, header.trigger_time_us)?;
+    match format {
+        Format::Ascii => write!(writer, "ASCII\r\n")?,
+        Format::Binary32 => write!(writer, "BINARY32\r\n")?,
+    }
+    write!(writer, "1\r\n")?;
+    write!(writer, "0,0\r\n")?;
+    write!(writer, "0,0\r\n")
+}
+
+/// Writes the data file of the given capture.
+/// # Arguments
+/// * `writer` - The destination implementing the [`Write`] trait.
+/// * `header` - The [`Header`] struct containing the information on the recording.
+/// * `capture` - The [`capture::Capture`] containing the recorded frames.
+/// * `format` - The variant of the data file as a [`Format`].
+pub fn write_dat<const N: usize, const L: usize>(
+    writer: &mut impl Write,
+    header: &Header,
+    capture: &capture::Capture<N, L>,
+    format: Format,
+) -> io::Result<()> {
+    let period_us = 1_000_000 / header.adc_freq.hz();
+    let selection = capture.config().channels;
+    for (n, frame) in capture.frames().enumerate() {
+        let values = frame.iter().zip(selection).flat_map(|(raw, channels)| {
+            [raw.iwv, raw.v1wv, raw.v2wv]
+                .into_iter()
+                .take(channel_count(channels))
+        });
+        let timestamp = n as u32 * period_us;
+        match format {
+            Format::Ascii => {
+                write!(writer, "{},{}", n + 1, timestamp)?;
+                for value in values {
+                    write!(writer, ",{}", value)?;
+                }
+                write!(writer, "\r\n")?;
+            }
+            Format::Binary32 => {
+                writer.write_all(&(n as u32 + 1).to_le_bytes())?;
+                writer.write_all(&timestamp.to_le_bytes())?;
+                for value in values {
+                    writer.write_all(&value.to_le_bytes())?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Describes an analog channel of the configuration file.
+struct Channel {
+    name: &'static str,
+    phase: Phase,
+    unit: &'static str,
+    coefficients: Coefficients,
+}
+
+/// Represents the phase identifier of an ADC, a letter for the first three ADCs and a number for
+/// the others.
+#[derive(Copy, Clone)]
+struct Phase(usize);
+
+impl core::fmt::Display for Phase {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.0 {
+            0..=2 => write!(f, "{}", (b'A' + self.0 as u8) as char),
+            i => write!(f, "{}", i + 1),
+        }
+    }
+}
+
+/// Returns the analog channels of the given capture, following the channel selection of each ADC.
+/// # Arguments
+/// * `capture` - The [`capture::Capture`] containing the recorded frames.
+fn channels<const N: usize, const L: usize>(capture: &capture::Capture<N, L>) -> Vec<Channel> {
+    let mut channels = Vec::new();
+    for (i, (conversion, selection)) in capture
+        .conversion()
+        .iter()
+        .zip(capture.config().channels)
+        .enumerate()
+    {
+        let aux = if conversion.aux_temperature {
+            ("T", "degC")
+        } else {
+            ("V2", "V")
+        };
+        let all = [
+            ("I", "A", conversion.current),
+            ("V", "V", conversion.voltage),
+            (aux.0, aux.1, conversion.aux),
+        ];
+        for (name, unit, coefficients) in all.into_iter().take(channel_count(selection)) {
+            channels.push(Channel {
+                name,
+                phase: Phase(i),
+                unit,
+                coefficients,
+            });
+        }
+    }
+    channels
+}
+
+/// Returns the number of channels recorded for the given selection.
+/// # Arguments
+/// * `channels` - The selected channels as a [`Channels`] value.
+fn channel_count(channels: Channels) -> usize {
+    match channels {
+        Channels::Iwv => 1,
+        Channels::IwvV1wv => 2,
+        Channels::All | Channels::AllCrc => 3,
+    }
+}
+
+/// Writes a time in the `dd/mm/yyyy,hh:mm:ss.ssssss` format of the configuration file.
+/// # Arguments
+/// * `writer` - The destination implementing the [`Write`] trait.
+/// * `time_us` - The time in µs since the Unix epoch.
+fn write_time(writer: &mut impl Write, time_us: i64) -> io::Result<()> {
+    let days = time_us.div_euclid(86_400_000_000);
+    let time_us = time_us.rem_euclid(86_400_000_000);
+    // Civil date from the days since the Unix epoch, see
+    // http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    write!(
+        writer,
+        "{:02}/{:02}/{:04},{:02}:{:02}:{:02}.{:06}\r\n",
+        day,
+        month,
+        year,
+        time_us / 3_600_000_000,
+        time_us / 60_000_000 % 60,
+        time_us / 1_000_000 % 60,
+        time_us % 1_000_000
+    )
+}
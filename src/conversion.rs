@@ -0,0 +1,116 @@
+use super::*;
+
+/// Full-scale value of the current channel.
+pub(crate) const CURRENT_FULL_SCALE: f64 = 49.27;
+/// Full-scale value of the voltage channels.
+pub(crate) const VOLTAGE_FULL_SCALE: f64 = 788.0;
+
+/// Contains the coefficients of a linear conversion from a raw ADC value `x` to a metric `y`,
+/// computed as `y = scale * x + offset`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Coefficients {
+    /// Multiplier applied to the raw value.
+    pub scale: f32,
+    /// Offset added to the scaled value.
+    pub offset: f32,
+}
+
+impl Coefficients {
+    /// Creates new [`Coefficients`] folding the full-scale mapping of the ADC, the calibration
+    /// offset and the calibration gain together. The raw value is first mapped between
+    /// `-full_scale` and `full_scale`, then the calibration offset is subtracted and finally the
+    /// result is multiplied by the calibration gain.
+    /// # Arguments
+    /// * `full_scale` - The full-scale value of the channel.
+    /// * `offset` - The calibration offset of the channel.
+    /// * `gain` - The calibration gain of the channel.
+    pub fn new(full_scale: f32, offset: f32, gain: f32) -> Self {
+        let k = 2.0 * full_scale as f64 / 16_777_215.0;
+        Self {
+            scale: (k * gain as f64) as f32,
+            offset: ((8_388_608.0 * k - full_scale as f64 - offset as f64) * gain as f64) as f32,
+        }
+    }
+
+    /// Returns the given raw value converted using the coefficients.
+    /// # Arguments
+    /// * `x` - The raw ADC value to be converted.
+    #[inline(always)]
+    pub fn apply(&self, x: i32) -> f32 {
+        x as f32 * self.scale + self.offset
+    }
+}
+
+/// Contains the precomputed conversion coefficients for all the channels of an ADC. The conversion
+/// folds full-scale mapping, calibration offset and calibration gain into a single [`Coefficients`]
+/// pair per channel, so that converting a [`RawMeasurement`] costs one multiply-add per channel.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Conversion {
+    /// Conversion coefficients for the current channel.
+    pub current: Coefficients,
+    /// Conversion coefficients for the voltage channel.
+    pub voltage: Coefficients,
+    /// Conversion coefficients for the auxiliary channel.
+    pub aux: Coefficients,
+    /// `true` if the auxiliary channel is measuring the temperature, `false` if it is measuring a
+    /// voltage.
+    pub aux_temperature: bool,
+}
+
+impl Conversion {
+    /// Creates a new [`Conversion`] given the chip, its configuration and its calibration values.
+    /// Auxiliary calibration values set to [`None`] default to an offset of `0.0` and a gain of
+    /// `1.0`. A temperature calibration set to [`None`] defaults to the datasheet values for the
+    /// configured bandwidth, with a TEMPOS value of `0`.
+    /// # Arguments
+    /// * `chip` - The chip version as a [`Chip`].
+    /// * `config` - The [`Config`] struct containing the configuration of the ADC.
+    /// * `calibration` - The [`Calibration`] struct containing the calibration values of the ADC.
+    pub fn new(chip: Chip, config: &Config, calibration: &Calibration) -> Self {
+        let aux_offset = calibration.offset.aux.unwrap_or(0.0);
+        let aux_gain = calibration.gain.aux.unwrap_or(1.0);
+        let aux_temperature = chip == Chip::ADE7912 || config.temp_en;
+        Self {
+            current: Coefficients::new(
+                CURRENT_FULL_SCALE as f32,
+                calibration.offset.current,
+                calibration.gain.current,
+            ),
+            voltage: Coefficients::new(
+                VOLTAGE_FULL_SCALE as f32,
+                calibration.offset.voltage,
+                calibration.gain.voltage,
+            ),
+            aux: if aux_temperature {
+                calibration
+                    .temperature
+                    .unwrap_or(TemperatureCalibration::from_tempos(0, config.bw))
+                    .coefficients()
+            } else {
+                Coefficients::new(VOLTAGE_FULL_SCALE as f32, aux_offset, aux_gain)
+            },
+            aux_temperature,
+        }
+    }
+
+    /// Returns the given [`RawMeasurement`] converted to a [`Measurement`].
+    /// # Arguments
+    /// * `raw_measurement` - The [`RawMeasurement`] to be converted.
+    #[inline]
+    pub fn convert(&self, raw_measurement: &RawMeasurement) -> Measurement {
+        let aux = self.aux.apply(raw_measurement.v2wv);
+        Measurement {
+            current: self.current.apply(raw_measurement.iwv),
+            voltage: self.voltage.apply(raw_measurement.v1wv),
+            aux: if self.aux_temperature {
+                MeasurementAux::Temperature(aux)
+            } else {
+                MeasurementAux::Voltage(aux)
+            },
+        }
+    }
+}
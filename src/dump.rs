@@ -0,0 +1,110 @@
+//! Register dumps for diagnostics. A [`RegisterDump`] holds the content of every readable register
+//! of an ADC, decoded into the driver types, and can be compared with the content expected from
+//! the configuration stored in the driver to detect devices that were reset or reconfigured behind
+//! the back of the driver.
+
+use super::*;
+
+/// Contains the content of the readable registers of an ADC. The waveform registers, the ADC CRC,
+/// STATUS0 and the counter snapshot are read in a single burst, so they refer to the same ADC
+/// output. The write-only LOCK and SYNC_SNAP registers are not part of the dump.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegisterDump {
+    /// Content of the IWV register.
+    pub iwv: i32,
+    /// Content of the V1WV register.
+    pub v1wv: i32,
+    /// Content of the V2WV register.
+    pub v2wv: i32,
+    /// Content of the ADC_CRC register.
+    pub adc_crc: u16,
+    /// Content of the CTRL_CRC register, that is the CRC of the configuration registers.
+    pub ctrl_crc: u16,
+    /// Content of the CNT_SNAPSHOT register.
+    pub cnt_snapshot: u16,
+    /// Content of the CONFIG register as a [`Config`].
+    pub config: Config,
+    /// Content of the STATUS0 register as a [`Status0`].
+    pub status0: Status0,
+    /// Content of the STATUS1 register as a [`Status1`].
+    pub status1: Status1,
+    /// Content of the EMI_CTRL register as an [`EmiCtrl`].
+    pub emi_ctrl: EmiCtrl,
+    /// Content of the TEMPOS register.
+    pub tempos: i8,
+}
+
+/// Contains the expected content of the configuration and status registers of an ADC, derived
+/// from the configuration stored in the driver.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Expected {
+    /// Expected content of the CONFIG register.
+    pub config: Config,
+    /// Expected content of the EMI_CTRL register.
+    pub emi_ctrl: EmiCtrl,
+    /// `true` if the configuration registers are expected to be protected.
+    pub locked: bool,
+}
+
+/// Represents a register whose content differs from the expected one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mismatch<T> {
+    /// Expected content of the register.
+    pub expected: T,
+    /// Actual content of the register.
+    pub actual: T,
+}
+
+impl<T: PartialEq> Mismatch<T> {
+    fn check(expected: T, actual: T) -> Option<Self> {
+        (expected != actual).then_some(Self { expected, actual })
+    }
+}
+
+/// Contains the differences between a [`RegisterDump`] and the [`Expected`] content. A field is
+/// [`None`] if the register content matches the expected one.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegisterDiff {
+    /// Mismatch of the CONFIG register.
+    pub config: Option<Mismatch<Config>>,
+    /// Mismatch of the EMI_CTRL register.
+    pub emi_ctrl: Option<Mismatch<EmiCtrl>>,
+    /// Mismatch of the STATUS0 register. Only the `reset_on` and `ic_prot` fields are compared,
+    /// `crc_stat` is copied from the actual content.
+    pub status0: Option<Mismatch<Status0>>,
+}
+
+impl RegisterDiff {
+    /// Returns `true` if all the registers match the expected content.
+    pub fn is_empty(&self) -> bool {
+        self.config.is_none() && self.emi_ctrl.is_none() && self.status0.is_none()
+    }
+}
+
+impl RegisterDump {
+    /// Returns the differences between the dump and the expected content.
+    /// # Arguments
+    /// * `expected` - The [`Expected`] content of the registers.
+    pub fn diff(&self, expected: &Expected) -> RegisterDiff {
+        RegisterDiff {
+            config: Mismatch::check(expected.config, self.config),
+            emi_ctrl: Mismatch::check(expected.emi_ctrl, self.emi_ctrl),
+            status0: Mismatch::check(
+                Status0 {
+                    reset_on: false,
+                    crc_stat: self.status0.crc_stat,
+                    ic_prot: expected.locked,
+                },
+                self.status0,
+            ),
+        }
+    }
+}
@@ -1,23 +1,51 @@
 #![doc = include_str!("../README.md")]
-#![no_std]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 use core::marker::PhantomData;
 
-use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::blocking::delay::{DelayMs, DelayUs};
 use embedded_hal::blocking::spi;
-use embedded_hal::digital::v2::OutputPin;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
 
+pub use conversion::*;
 pub use register::*;
+pub use retry::*;
+pub use source::*;
+pub use temperature::*;
 
 mod ade791x;
+pub mod capture;
+mod codec;
+#[cfg(feature = "std")]
+pub mod comtrade;
+mod conversion;
+pub mod dump;
 pub mod poly;
+pub mod profile;
+pub mod record;
 mod register;
+mod retry;
+mod source;
+pub mod split;
+pub mod sync;
+mod temperature;
+pub mod tracking;
 
-/// Represents a single ADE7912/ADE7913 3-Channel, Isolated, Sigma-Delta ADC with SPI.
-pub struct Ade791x<SPI, CS> {
-    adc: poly::Ade791x<SPI, CS, 1>,
+/// Number of ADC output periods after which a DREADY line that does not toggle is considered
+/// stuck.
+pub const DREADY_TIMEOUT_PERIODS: u32 = 4;
+
+/// Represents a single ADE7912/ADE7913 3-Channel, Isolated, Sigma-Delta ADC with SPI. The DREADY
+/// input pin is owned by the driver only if given with [`Self::with_dready()`].
+pub struct Ade791x<SPI, CS, DR = NoDready> {
+    adc: poly::Ade791x<SPI, CS, 1, DR>,
 }
 
+/// Placeholder for the DREADY input pin when it is not owned by the driver.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NoDready;
+
 impl<SPI, CS, S, P> Ade791x<SPI, CS>
 where
     SPI: spi::Transfer<u8, Error = S>,
@@ -45,6 +73,28 @@ where
         }
     }
 
+    /// Gives the DREADY input pin to the driver, enabling [`Self::next_measurement()`] and
+    /// [`Self::collect()`].
+    /// # Arguments
+    /// * `dready` - The DREADY input pin implementing the [`InputPin`] trait. Its error type may
+    ///   differ from the one of the CS pins, as long as it converts into it, and its errors are
+    ///   reported as [`Error::PinError`].
+    pub fn with_dready<DR>(self, dready: DR) -> Ade791x<SPI, CS, DR>
+    where
+        DR: InputPin,
+        P: From<DR::Error>,
+    {
+        Ade791x {
+            adc: self.adc.with_dready(dready),
+        }
+    }
+}
+
+impl<SPI, CS, DR, S, P> Ade791x<SPI, CS, DR>
+where
+    SPI: spi::Transfer<u8, Error = S>,
+    CS: OutputPin<Error = P>,
+{
     /// Initializes the ADC, applying the given configuration. After this method, the ADC is ready
     /// to use.
     /// # Arguments
@@ -87,11 +137,36 @@ where
         self.adc.wakeup()
     }
 
+    /// Enables or disables the protection of the internal registers of the ADC. See
+    /// [`poly::Ade791x::set_protection()`].
+    /// # Arguments
+    /// * `enabled` - `true` to lock the registers, `false` to leave them unlocked.
+    pub fn set_protection(&mut self, enabled: bool) {
+        self.adc.set_protection(enabled)
+    }
+
+    /// Returns `true` if the protection of the internal registers is enabled, `false` otherwise.
+    pub fn protection(&self) -> bool {
+        self.adc.protection()
+    }
+
+    /// Runs the given closure in a [`poly::Session`], unlocking the internal registers of the ADC
+    /// once for all the writings performed by the closure. The registers are always locked again
+    /// and the lock is verified. See [`poly::Ade791x::configure()`].
+    /// # Arguments
+    /// * `f` - The closure performing the writings through the [`poly::Session`].
+    pub fn configure<T, F>(&mut self, f: F) -> Result<T, Error<S, P>>
+    where
+        F: FnOnce(&mut poly::Session<'_, SPI, CS, 1, DR>) -> Result<T, Error<S, P>>,
+    {
+        self.adc.configure(f)
+    }
+
     /// Returns the latest available measurement from the ADC as a [`RawMeasurement`] struct. Call
     /// this method inside the ISR from the DREADY pin to get a new measurement as soon as it's
     /// ready. This method does not convert the received data. To get converted metrics, use
-    /// [`Self::get_measurement()`] instead. This method does not perform CRC checks on received
-    /// data.
+    /// [`Self::get_measurement()`] instead. CRC checks on received data are performed only if
+    /// enabled with [`Self::set_channels()`].
     pub fn get_raw_measurement(&mut self) -> Result<RawMeasurement, Error<S, P>> {
         self.adc.get_raw_measurement().map(|m| m[0])
     }
@@ -99,14 +174,204 @@ where
     /// Returns the latest available measurement from the ADC as a [`Measurement`] struct. Call this
     /// method inside the ISR from the DREADY pin to get a new measurement as soon as it's ready.
     /// This method converts raw data to voltage, current and temperature measurements using the
-    /// provided calibration values. This method does not perform CRC checks on received data.
+    /// provided calibration values. CRC checks on received data are performed only if enabled with
+    /// [`Self::set_channels()`].
     pub fn get_measurement(&mut self) -> Result<Measurement, Error<S, P>> {
         self.adc.get_measurement().map(|m| m[0])
     }
+
+    /// Returns the temperature of the die in °C, averaged over the given number of samples. A new
+    /// sample is read every millisecond, so that consecutive readings never refer to the same ADC
+    /// output. This method returns [`Error::TemperatureDisabled`] if the auxiliary channel is not
+    /// measuring the temperature, i.e. for the ADE7913 with `temp_en = false` in [`Config`].
+    /// # Arguments
+    /// * `delay` - The delay source implementing the [`DelayMs`] trait.
+    /// * `samples` - The number of samples to average.
+    pub fn read_temperature(
+        &mut self,
+        delay: &mut dyn DelayMs<u32>,
+        samples: u32,
+    ) -> Result<f32, Error<S, P>> {
+        self.adc.read_temperature(delay, samples)?[0]
+            .ok_or(Error::TemperatureDisabled { device: 0 })
+    }
+
+    /// Sets the schedule of the interleaved temperature sampling. When a schedule is active,
+    /// [`Self::get_measurement()`] periodically switches the second voltage channel to the internal
+    /// temperature sensor, discarding the settling samples, and then restores the voltage
+    /// measurement. During the switch, the auxiliary field of [`Measurement`] is set to
+    /// [`MeasurementAux::Unavailable`], and the measured temperatures can be retrieved using
+    /// [`Self::take_temperature()`]. The schedule is only permitted for the ADE7913 with
+    /// `temp_en = false` in [`Config`], with the second voltage channel read as selected with
+    /// [`Self::set_channels()`]. The schedule only advances in [`Self::get_measurement()`] and
+    /// [`Self::get_tracked_measurement()`]: the raw measurements and the samples do not advance it,
+    /// leaving the channel in its current mode.
+    /// # Arguments
+    /// * `schedule` - The [`TemperatureSchedule`] to follow, or [`None`] to disable it.
+    pub fn set_temperature_schedule(
+        &mut self,
+        schedule: Option<TemperatureSchedule>,
+    ) -> Result<(), Error<S, P>> {
+        self.adc.set_temperature_schedule([schedule])
+    }
+
+    /// Returns the latest temperature in °C measured following the schedule set with
+    /// [`Self::set_temperature_schedule()`], if a new one is available.
+    pub fn take_temperature(&mut self) -> Option<f32> {
+        self.adc.take_temperature()[0]
+    }
+
+    /// Sets new calibration values for the ADC, updating the precomputed conversion coefficients
+    /// used by [`Self::get_measurement()`]. Calibration values set to [`None`] are resolved as in
+    /// [`Self::init()`].
+    /// # Arguments
+    /// * `calibration` - The [`Calibration`] struct containing the calibration values for the ADC.
+    pub fn set_calibration(&mut self, calibration: Calibration) {
+        self.adc.set_calibration([calibration])
+    }
+
+    /// Returns the precomputed [`Conversion`] used by [`Self::get_measurement()`] to convert raw
+    /// data.
+    pub fn conversion(&self) -> Conversion {
+        self.adc.conversion()[0]
+    }
+
+    /// Selects the channels read by [`Self::get_raw_measurement()`] and
+    /// [`Self::get_measurement()`]. Reading fewer channels shortens the burst transaction, reducing
+    /// the SPI time spent in the DREADY ISR. The fields corresponding to the channels that are not
+    /// read are set to zero in [`RawMeasurement`], while in [`Measurement`] the voltage is set to
+    /// NaN and the auxiliary field to [`MeasurementAux::Unavailable`]. The second
+    /// voltage channel must be read while an interleaved temperature sampling is active, otherwise
+    /// a [`Error::TemperatureScheduleNotPermitted`] is returned.
+    /// # Arguments
+    /// * `channels` - The set of channels to read as a [`Channels`] value.
+    pub fn set_channels(&mut self, channels: Channels) -> Result<(), Error<S, P>> {
+        self.adc.set_channels([channels])
+    }
+
+    /// Sets the [`RetryPolicy`] applied to the SPI transactions of the ADC. The checked register
+    /// writings of [`Self::init()`] are retried with the backoff of the policy, while the burst
+    /// reads of the measurements and samples are retried immediately.
+    /// # Arguments
+    /// * `retry` - The [`RetryPolicy`] struct to apply.
+    pub fn set_retry_policy(&mut self, retry: RetryPolicy) {
+        self.adc.set_retry_policy(retry)
+    }
+
+    /// Returns the [`RetryPolicy`] applied to the SPI transactions, as set with
+    /// [`Self::set_retry_policy()`].
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.adc.retry_policy()
+    }
+
+    /// Returns the [`RetryCounters`] of the ADC.
+    pub fn retry_counters(&self) -> RetryCounters {
+        self.adc.retry_counters()[0]
+    }
+
+    /// Resets the [`RetryCounters`] of the ADC to zero.
+    pub fn reset_retry_counters(&mut self) {
+        self.adc.reset_retry_counters()
+    }
+
+    /// Returns the latest available sample from the ADC as a [`Sample`] struct. The sample is read
+    /// in a single burst transaction and contains, alongside the raw waveforms, the ADC CRC, the
+    /// STATUS0 register and the counter snapshot. Use this method instead of
+    /// [`Self::get_raw_measurement()`] to perform integrity, reset and timing checks without
+    /// additional SPI traffic.
+    pub fn get_sample(&mut self) -> Result<Sample, Error<S, P>> {
+        self.adc.get_sample().map(|s| s[0])
+    }
+
+    /// Reads every readable register of the ADC into a [`dump::RegisterDump`] struct.
+    pub fn dump_registers(&mut self) -> Result<dump::RegisterDump, Error<S, P>> {
+        self.adc.dump_registers().map(|d| d[0])
+    }
+
+    /// Returns the expected content of the configuration and status registers of the ADC, derived
+    /// from the configuration given to [`Self::init()`], as a [`dump::Expected`] struct.
+    pub fn expected_registers(&self) -> dump::Expected {
+        self.adc.expected_registers()[0]
+    }
+
+    /// Dumps the registers of the ADC and compares them with the expected content, returning the
+    /// differences as a [`dump::RegisterDiff`] struct.
+    pub fn diff_registers(&mut self) -> Result<dump::RegisterDiff, Error<S, P>> {
+        self.adc.diff_registers().map(|d| d[0])
+    }
+
+    /// Returns the latest available measurement from the ADC as a [`Measurement`] struct, stamped
+    /// with its sample index. Missed samples and duplicate reads are detected by comparing the
+    /// given timestamp with the one of the previous read. See [`tracking::SampleTracker`].
+    /// # Arguments
+    /// * `timestamp_us` - The timestamp of the read in µs, taken from a monotonic clock.
+    pub fn get_tracked_measurement(
+        &mut self,
+        timestamp_us: u64,
+    ) -> Result<tracking::Tracked<Measurement>, Error<S, P>> {
+        self.adc
+            .get_tracked_measurement(timestamp_us)
+            .map(|t| t.map(|m| m[0]))
+    }
+
+    /// Returns the [`record::Header`] describing the ADC, to start a recording of its samples with
+    /// a [`record::Recorder`]. See the [`record`] module.
+    /// # Arguments
+    /// * `status` - `true` to record the ADC CRC, the STATUS0 register and the counter snapshot
+    ///   alongside the waveforms, `false` otherwise.
+    /// * `delta` - `true` to delta compress the waveforms, `false` otherwise.
+    pub fn recording_header(&self, status: bool, delta: bool) -> record::Header<1> {
+        self.adc.recording_header(status, delta)
+    }
+
+    /// Returns the [`tracking::SampleTracker`] used by [`Self::get_tracked_measurement()`], holding
+    /// the counters of missed samples and duplicate reads.
+    pub fn tracker(&self) -> &tracking::SampleTracker {
+        self.adc.tracker()
+    }
+}
+
+impl<SPI, CS, DR, S, P> Ade791x<SPI, CS, DR>
+where
+    SPI: spi::Transfer<u8, Error = S>,
+    CS: OutputPin<Error = P>,
+    DR: InputPin,
+    P: From<DR::Error>,
+{
+    /// Waits for the next falling edge of the DREADY signal and returns the new measurement from the
+    /// ADC as a [`Measurement`] struct. The DREADY line is polled every microsecond. If it does not
+    /// toggle within [`DREADY_TIMEOUT_PERIODS`] ADC output periods, the line is considered stuck and
+    /// [`Error::DreadyStuckLow`] or [`Error::DreadyStuckHigh`] is returned.
+    /// # Arguments
+    /// * `delay` - The delay source implementing the [`DelayUs`] trait.
+    pub fn next_measurement(
+        &mut self,
+        delay: &mut dyn DelayUs<u32>,
+    ) -> Result<Measurement, Error<S, P>> {
+        self.adc.next_measurement(delay).map(|m| m[0])
+    }
+
+    /// Fills the given buffer with consecutive measurements from the ADC, waiting for the falling
+    /// edge of the DREADY signal before each read. See [`Self::next_measurement()`].
+    /// # Arguments
+    /// * `delay` - The delay source implementing the [`DelayUs`] trait.
+    /// * `buffer` - The buffer of [`Measurement`] structs to fill.
+    pub fn collect(
+        &mut self,
+        delay: &mut dyn DelayUs<u32>,
+        buffer: &mut [Measurement],
+    ) -> Result<(), Error<S, P>> {
+        for val in buffer.iter_mut() {
+            *val = self.next_measurement(delay)?;
+        }
+        Ok(())
+    }
 }
 
 /// Contains the raw values coming from the ADC.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RawMeasurement {
     /// Raw current channel value.
     pub iwv: i32,
@@ -116,12 +381,71 @@ pub struct RawMeasurement {
     pub v2wv: i32,
 }
 
+/// Contains a full sample coming from the ADC, that is the raw waveforms together with the
+/// registers that follow them in a burst read.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sample {
+    /// Raw waveform values as a [`RawMeasurement`].
+    pub raw: RawMeasurement,
+    /// CRC of the `iwv`, `v1wv` and `v2wv` fields, as computed by the ADC.
+    pub adc_crc: u16,
+    /// Content of the STATUS0 register as a [`Status0`].
+    pub status0: Status0,
+    /// Snapshot value of the internal counter, latched by the last snap command. The counter wraps
+    /// around at every output period, so it cannot be used to number the samples. Use
+    /// [`tracking::SampleTracker`] instead.
+    pub cnt_snapshot: u16,
+}
+
+impl From<RawMeasurement> for Sample {
+    fn from(x: RawMeasurement) -> Self {
+        Self {
+            raw: x,
+            adc_crc: 0,
+            status0: Status0::default(),
+            cnt_snapshot: 0,
+        }
+    }
+}
+
+impl Sample {
+    /// Returns `true` if the CRC computed on the received waveforms matches the `adc_crc` field,
+    /// `false` otherwise. The CRC is computed using the CRC-16-CCITT algorithm, as described in
+    /// the ADE7912/ADE7913 Datasheet.
+    pub fn is_crc_valid(&self) -> bool {
+        self.raw.crc() == self.adc_crc
+    }
+
+    /// Returns `true` if the ADC has been reset and it is waiting to be configured again, `false`
+    /// otherwise.
+    pub fn is_reset(&self) -> bool {
+        self.status0.reset_on
+    }
+}
+
+impl RawMeasurement {
+    /// Returns the CRC-16-CCITT (polynomial 0x1021, initial value 0xFFFF) of the waveforms, computed
+    /// on the 24-bit big-endian representation of `iwv`, `v1wv` and `v2wv`.
+    pub(crate) fn crc(&self) -> u16 {
+        codec::crc16(
+            [self.iwv, self.v1wv, self.v2wv]
+                .iter()
+                .flat_map(|wv| wv.to_be_bytes().into_iter().skip(1)),
+        )
+    }
+}
+
 /// Contains the converted metrics coming from the ADC.
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Measurement {
     /// Current value in Amperes.
     pub current: f32,
-    /// Voltage value in Volts.
+    /// Voltage value in Volts, or NaN if the voltage channel is not read, as selected with
+    /// [`Ade791x::set_channels()`].
     pub voltage: f32,
     /// Auxiliary metric value as a [`MeasurementAux`]. This field can be a second voltage
     /// measurement in Volts for the ADE7913 or a temperature measurement in °C for the ADE7912 or
@@ -131,30 +455,50 @@ pub struct Measurement {
 
 /// Represents the possible auxiliary measurement metrics.
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum MeasurementAux {
     Voltage(f32),
     Temperature(f32),
+    /// No value is available for this sample: the second voltage channel is not read, as selected
+    /// with [`Ade791x::set_channels()`], or it is temporarily measuring the temperature, following
+    /// a [`TemperatureSchedule`].
+    Unavailable,
 }
 
 /// Contains the calibration values for the ADC.
 #[derive(Default, Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Calibration {
     /// Calibration offset as a [`CalibrationOffset`].
     pub offset: CalibrationOffset,
     /// Calibration gain as a [`CalibrationGain`].
     pub gain: CalibrationGain,
+    /// Calibration of the internal temperature sensor as a [`TemperatureCalibration`]. Set this
+    /// field to [`None`] to automatically calibrate the sensor based on the TEMPOS register and on
+    /// the bandwidth setting.
+    pub temperature: Option<TemperatureCalibration>,
+    /// Calibration temperature coefficients as a [`CalibrationTempCo`].
+    pub tempco: CalibrationTempCo,
 }
 
 /// Contains the calibration offsets, that can be obtained by reading the ADC measurements with the
 /// default calibration values and no load applied.
 #[derive(Default, Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct CalibrationOffset {
     /// Calibration offset for the current channel.
     pub current: f32,
     /// Calibration offset for the voltage channel.
     pub voltage: f32,
-    /// Calibration offset for the auxiliary channel. Set this field to [`None`] to automatically
-    /// set the auxiliary offset based on the internal values.
+    /// Calibration offset for the auxiliary voltage channel. Set this field to [`None`] to use no
+    /// offset. This field is not used when the auxiliary channel measures the temperature, see
+    /// [`TemperatureCalibration`] instead.
     pub aux: Option<f32>,
 }
 
@@ -162,13 +506,17 @@ pub struct CalibrationOffset {
 /// dividing it with the ADC measurements with only the offset values set, while leaving the
 /// multipliers to their default values.
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct CalibrationGain {
     /// Calibration gain for the current channel.
     pub current: f32,
     /// Calibration voltage for the voltage channel.
     pub voltage: f32,
-    /// Calibration gain for the auxiliary channel. Set this field to [`None`] to automatically
-    /// set the auxiliary offset based on the internal values.
+    /// Calibration gain for the auxiliary voltage channel. Set this field to [`None`] to use a
+    /// unitary gain. This field is not used when the auxiliary channel measures the temperature,
+    /// see [`TemperatureCalibration`] instead.
     pub aux: Option<f32>,
 }
 
@@ -182,22 +530,280 @@ impl Default for CalibrationGain {
     }
 }
 
+/// Contains the temperature coefficients of the calibration gains, that can be obtained by
+/// calibrating the gains at two or more temperatures and fitting them with [`TempCo::fit_linear()`]
+/// or [`TempCo::fit_table()`]. The coefficients are applied using the latest temperature measured
+/// by the ADC, either on the auxiliary channel or following a [`TemperatureSchedule`]. No
+/// compensation is applied until the first temperature is measured.
+#[derive(Default, Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct CalibrationTempCo {
+    /// Temperature coefficient for the current channel.
+    pub current: TempCo,
+    /// Temperature coefficient for the voltage channel.
+    pub voltage: TempCo,
+}
+
+impl CalibrationTempCo {
+    /// Applies the temperature compensation to the current and voltage fields of the given
+    /// measurement.
+    /// # Arguments
+    /// * `temperature` - The die temperature in °C.
+    /// * `measurement` - The [`Measurement`] struct to compensate.
+    pub(crate) fn compensate(&self, temperature: f32, measurement: &mut Measurement) {
+        if self.current != TempCo::None {
+            measurement.current *= self.current.factor(temperature);
+        }
+        if self.voltage != TempCo::None {
+            measurement.voltage *= self.voltage.factor(temperature);
+        }
+    }
+}
+
 /// Represents the chips of the ADE791x family.
-#[derive(PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Chip {
     ADE7912,
     ADE7913,
 }
 
-/// Represents the possible errors.
-#[repr(u8)]
+/// Represents the sets of channels that can be read with a single burst transaction.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Channels {
+    /// Current channel only (IWV).
+    Iwv,
+    /// Current and voltage channels (IWV and V1WV).
+    IwvV1wv,
+    /// All the channels (IWV, V1WV and V2WV).
+    #[default]
+    All,
+    /// All the channels followed by the ADC CRC, which is checked on every read.
+    AllCrc,
+}
+
+impl Channels {
+    /// Returns the number of bytes of the burst read needed to get the channels.
+    pub(crate) fn burst_len(&self) -> usize {
+        match self {
+            Channels::Iwv => 3,
+            Channels::IwvV1wv => 6,
+            Channels::All => 9,
+            Channels::AllCrc => 11,
+        }
+    }
+
+    /// Returns `true` if the auxiliary channel (V2WV) is read, `false` otherwise.
+    pub(crate) fn has_aux(&self) -> bool {
+        matches!(self, Channels::All | Channels::AllCrc)
+    }
+
+    /// Flags the fields of a [`Measurement`] struct that correspond to the channels that are not
+    /// read, instead of the values converted from zero: the voltage is set to NaN and the auxiliary
+    /// field to [`MeasurementAux::Unavailable`].
+    /// # Arguments
+    /// * `measurement` - The [`Measurement`] struct converted from the read channels.
+    pub(crate) fn mask(&self, measurement: &mut Measurement) {
+        if *self == Channels::Iwv {
+            measurement.voltage = f32::NAN;
+        }
+        if !self.has_aux() {
+            measurement.aux = MeasurementAux::Unavailable;
+        }
+    }
+}
+
+/// Represents the possible errors. The errors referring to a single ADC carry its index in the
+/// polyphase metering system as `device`, which is always `0` for [`Ade791x`].
 #[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub enum Error<S, P> {
+    /// The SPI interface returned the given error.
     SpiError(S),
+    /// A CS or DREADY pin returned the given error.
     PinError(P),
-    ResetTimeout,
-    ReadOnlyRegister,
-    WriteOnlyRegister,
-    BurstReadNotPermitted,
-    RegisterContentMismatch,
+    /// The ADC is still in reset after the wait period.
+    ResetTimeout { device: usize },
+    /// The given register cannot be written.
+    ReadOnlyRegister(Register),
+    /// The given register cannot be read.
+    WriteOnlyRegister(Register),
+    /// A burst read cannot start from the given register.
+    BurstReadNotPermitted(Register),
+    /// The content read back from a register does not match the written one, e.g. because the
+    /// registers of the ADC are locked or the ADC has been reset.
+    RegisterContentMismatch {
+        device: usize,
+        register: Register,
+        expected: u8,
+        actual: u8,
+    },
+    /// The CRC computed on the received waveforms, `expected`, does not match the ADC CRC read
+    /// with them, `actual`.
+    CrcMismatch {
+        device: usize,
+        expected: u16,
+        actual: u16,
+    },
+    /// The auxiliary channel of the ADC is not measuring the temperature.
+    TemperatureDisabled { device: usize },
+    /// The interleaved temperature sampling is only permitted for the ADE7913 measuring the
+    /// second voltage, with the second voltage channel read.
+    TemperatureScheduleNotPermitted { device: usize },
+    /// The internal registers of the ADC are not protected after locking them, i.e. the `ic_prot`
+    /// bit of the STATUS0 register is cleared.
+    LockFailed { device: usize },
+    /// The ADC cannot be powered-down on its own, as it generates the DREADY signal or provides
+    /// the clock to an ADC that is not powered-down.
+    PowerdownNotPermitted { device: usize },
+    /// The ADC cannot be woken-up on its own, as the ADC providing its clock is powered-down.
+    WakeupNotPermitted { device: usize },
+    /// The EMI settings of the ADC have no slot enabled.
+    EmiCtrlEmpty { device: usize },
+    /// The EMI settings of the ADC share a slot with another ADC, with the strict check enabled.
+    EmiCtrlOverlap { device: usize },
+    /// The topology of the polyphase metering system is not valid.
+    Topology(poly::TopologyError),
+    /// The DREADY pin did not go high within the timeout.
+    DreadyStuckLow,
+    /// The DREADY pin did not go low within the timeout.
+    DreadyStuckHigh,
+}
+
+/// Represents the action suggested to recover from an [`Error`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Recovery {
+    /// The error is transient, e.g. a glitch on the bus, and the operation can be retried.
+    Retry,
+    /// The state of the ADCs does not match the driver anymore, e.g. because an ADC has been
+    /// reset or lost its clock, and the ADCs must be initialized again.
+    Reinit,
+    /// The error is caused by the configuration or by the usage of the driver, and retrying will
+    /// not help.
+    Fail,
+}
+
+impl<S, P> Error<S, P> {
+    /// Returns the [`Recovery`] action suggested for the error. A [`Error::RegisterContentMismatch`]
+    /// is classified as [`Recovery::Reinit`] even if it is retried by the [`RetryPolicy`]: the
+    /// driver retries the single writing in place, while a mismatch reaching the caller has already
+    /// survived those retries and means that the ADC does not accept the configuration anymore,
+    /// e.g. because it has been reset or its registers are locked.
+    pub fn recovery(&self) -> Recovery {
+        match self {
+            Error::SpiError(_) | Error::PinError(_) | Error::CrcMismatch { .. } => Recovery::Retry,
+            Error::ResetTimeout { .. }
+            | Error::RegisterContentMismatch { .. }
+            | Error::LockFailed { .. }
+            | Error::DreadyStuckLow
+            | Error::DreadyStuckHigh => Recovery::Reinit,
+            Error::ReadOnlyRegister(_)
+            | Error::WriteOnlyRegister(_)
+            | Error::BurstReadNotPermitted(_)
+            | Error::TemperatureDisabled { .. }
+            | Error::TemperatureScheduleNotPermitted { .. }
+            | Error::PowerdownNotPermitted { .. }
+            | Error::WakeupNotPermitted { .. }
+            | Error::EmiCtrlEmpty { .. }
+            | Error::EmiCtrlOverlap { .. }
+            | Error::Topology(_) => Recovery::Fail,
+        }
+    }
+
+    /// Returns `true` if the error is transient and the operation can be retried, `false`
+    /// otherwise. See [`Self::recovery()`].
+    pub fn is_transient(&self) -> bool {
+        self.recovery() == Recovery::Retry
+    }
+
+    /// Returns the index of the ADC the error refers to, if any.
+    pub fn device(&self) -> Option<usize> {
+        match self {
+            Error::ResetTimeout { device }
+            | Error::RegisterContentMismatch { device, .. }
+            | Error::CrcMismatch { device, .. }
+            | Error::LockFailed { device }
+            | Error::TemperatureDisabled { device }
+            | Error::TemperatureScheduleNotPermitted { device }
+            | Error::PowerdownNotPermitted { device }
+            | Error::WakeupNotPermitted { device }
+            | Error::EmiCtrlEmpty { device }
+            | Error::EmiCtrlOverlap { device } => Some(*device),
+            _ => None,
+        }
+    }
+}
+
+impl<S: core::fmt::Debug, P: core::fmt::Debug> core::fmt::Display for Error<S, P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::SpiError(e) => write!(f, "SPI error: {e:?}"),
+            Error::PinError(e) => write!(f, "pin error: {e:?}"),
+            Error::ResetTimeout { device } => write!(f, "ADC {device} still in reset"),
+            Error::ReadOnlyRegister(register) => write!(f, "{register:?} is read-only"),
+            Error::WriteOnlyRegister(register) => write!(f, "{register:?} is write-only"),
+            Error::BurstReadNotPermitted(register) => {
+                write!(f, "burst read not permitted from {register:?}")
+            }
+            Error::RegisterContentMismatch {
+                device,
+                register,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "ADC {device}: {register:?} reads {actual:#04x} instead of {expected:#04x}"
+            ),
+            Error::CrcMismatch {
+                device,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "ADC {device}: CRC {actual:#06x} does not match the waveforms ({expected:#06x})"
+            ),
+            Error::TemperatureDisabled { device } => {
+                write!(f, "ADC {device} is not measuring the temperature")
+            }
+            Error::TemperatureScheduleNotPermitted { device } => write!(
+                f,
+                "ADC {device}: temperature schedule only permitted for the ADE7913 measuring \
+                 the second voltage"
+            ),
+            Error::LockFailed { device } => write!(f, "ADC {device}: registers not protected"),
+            Error::PowerdownNotPermitted { device } => write!(
+                f,
+                "ADC {device} generates DREADY or clocks a powered ADC and cannot be powered-down"
+            ),
+            Error::WakeupNotPermitted { device } => {
+                write!(
+                    f,
+                    "ADC {device} cannot be woken-up as its clock source is powered-down"
+                )
+            }
+            Error::EmiCtrlEmpty { device } => write!(f, "ADC {device}: no EMI_CTRL slot enabled"),
+            Error::EmiCtrlOverlap { device } => {
+                write!(f, "ADC {device}: EMI_CTRL slots shared with another ADC")
+            }
+            Error::Topology(e) => write!(f, "invalid topology: {e}"),
+            Error::DreadyStuckLow => write!(f, "DREADY stuck low"),
+            Error::DreadyStuckHigh => write!(f, "DREADY stuck high"),
+        }
+    }
+}
+
+impl<S: core::fmt::Debug, P: core::fmt::Debug> core::error::Error for Error<S, P> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Error::Topology(e) => Some(e),
+            _ => None,
+        }
+    }
 }
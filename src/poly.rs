@@ -1,12 +1,192 @@
 use super::*;
 
 /// Represents multiple ADE7912/ADE7913 3-Channel, Isolated, Sigma-Delta ADCs with SPI configured in
-/// a polyphase metering system.
-pub struct Ade791x<SPI, CS, const N: usize> {
+/// a polyphase metering system. The DREADY input pin is owned by the driver only if given with
+/// [`Self::with_dready()`].
+pub struct Ade791x<SPI, CS, const N: usize, DR = NoDready> {
     spi: SPI,
+    dready: DR,
+    adc_freq: AdcFreqVal,
     adcs: [ade791x::Ade791x<SPI, CS>; N],
+    topology: Topology<N>,
+    explicit_topology: bool,
+    protection: bool,
+    strict_emi_ctrl: bool,
+    tracker: tracking::SampleTracker,
 }
 
+/// Represents the clock source of an ADC in a polyphase metering system.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClockSource {
+    /// The ADC is clocked by a crystal or an external oscillator connected to its CLKIN pin.
+    Crystal,
+    /// The ADC is clocked by the CLKOUT signal of the ADC at the given index.
+    Clkout(usize),
+}
+
+/// Describes the topology of a polyphase metering system, that is how the clock is distributed
+/// between the ADCs and which ADC generates the DREADY signal. The ADC generating the DREADY signal
+/// is the reference for the synchronization of the internal counters.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Topology<const N: usize> {
+    /// Clock source of each ADC as a [`ClockSource`].
+    pub clock: [ClockSource; N],
+    /// Index of the ADC generating the DREADY signal.
+    pub dready: usize,
+}
+
+impl<const N: usize> Topology<N> {
+    /// Returns the topology of a daisy chain, where the first ADC is clocked by a crystal, each
+    /// following ADC is clocked by the CLKOUT signal of the previous one, and the last ADC generates
+    /// the DREADY signal.
+    pub fn chain() -> Self {
+        Self::from_order(core::array::from_fn(|i| i))
+    }
+
+    /// Returns the topology of a daisy chain following the given order of the ADCs. The first ADC
+    /// in the order is clocked by a crystal, each following ADC is clocked by the CLKOUT signal of
+    /// the previous one, and the last ADC generates the DREADY signal.
+    /// # Arguments
+    /// * `order` - The indexes of the ADCs in the order of the chain.
+    fn from_order(order: [usize; N]) -> Self {
+        let mut clock = [ClockSource::Crystal; N];
+        for k in 1..N {
+            clock[order[k]] = ClockSource::Clkout(order[k - 1]);
+        }
+        Self {
+            clock,
+            dready: order[N.max(1) - 1],
+        }
+    }
+
+    /// Returns the topology inferred from the configurations of the ADCs. The ADCs in CLKOUT mode
+    /// are chained in index order, followed by the ADC generating the DREADY signal.
+    /// # Arguments
+    /// * `config` - An array of [`Config`] structs containing the configurations for the ADCs.
+    fn infer(config: &[Config; N]) -> Self {
+        let mut order = [0; N];
+        let mut k = 0;
+        for clkout_en in [true, false] {
+            for (i, config) in config.iter().enumerate() {
+                if config.clkout_en == clkout_en {
+                    order[k] = i;
+                    k += 1;
+                }
+            }
+        }
+        Self::from_order(order)
+    }
+
+    /// Checks the structure of the topology, regardless of the configurations of the ADCs: the
+    /// indexes must be in range, the ADC generating the DREADY signal cannot provide the clock to
+    /// other ADCs and the clock chain of every ADC must reach a crystal.
+    fn check(&self) -> Result<(), TopologyError> {
+        if self.dready >= N {
+            return Err(TopologyError::InvalidIndex(self.dready));
+        }
+        for (i, clock) in self.clock.iter().enumerate() {
+            if let ClockSource::Clkout(j) = *clock {
+                if j >= N || j == i {
+                    return Err(TopologyError::InvalidIndex(j));
+                }
+                if j == self.dready {
+                    return Err(TopologyError::DreadyClkout(j));
+                }
+            }
+            let mut source = *clock;
+            for _ in 0..N {
+                match source {
+                    ClockSource::Crystal => break,
+                    ClockSource::Clkout(j) => source = self.clock[j],
+                }
+            }
+            if source != ClockSource::Crystal {
+                return Err(TopologyError::ClockLoop(i));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the topology against the configurations of the ADCs.
+    /// # Arguments
+    /// * `config` - An array of [`Config`] structs containing the configurations for the ADCs.
+    fn validate(&self, config: &[Config; N]) -> Result<(), TopologyError> {
+        self.check()?;
+        if config[self.dready].clkout_en {
+            return Err(TopologyError::DreadyMismatch(self.dready));
+        }
+        for clock in &self.clock {
+            if let ClockSource::Clkout(j) = *clock {
+                if !config[j].clkout_en {
+                    return Err(TopologyError::ClkoutDisabled(j));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Represents the possible errors in the topology of a polyphase metering system.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TopologyError {
+    /// No ADC is generating the DREADY signal, i.e. all the ADCs have `clkout_en = true`.
+    NoDreadySource,
+    /// More than one ADC is generating the DREADY signal, i.e. more than one ADC has
+    /// `clkout_en = false`.
+    MultipleDreadySources,
+    /// The ADCs do not share the same `adc_freq` setting.
+    AdcFreqMismatch,
+    /// The ADCs do not share the same `bw` setting.
+    BwMismatch,
+    /// The [`Topology`] refers to an ADC index that is out of range or to the ADC itself.
+    InvalidIndex(usize),
+    /// The ADC at the given index is expected to generate the DREADY signal, but it has
+    /// `clkout_en = true`.
+    DreadyMismatch(usize),
+    /// The ADC at the given index is expected to provide the clock to another ADC, but it has
+    /// `clkout_en = false`.
+    ClkoutDisabled(usize),
+    /// The ADC at the given index generates the DREADY signal, so it cannot provide the clock to
+    /// other ADCs.
+    DreadyClkout(usize),
+    /// The clock chain of the ADC at the given index loops, never reaching a crystal.
+    ClockLoop(usize),
+}
+
+impl core::fmt::Display for TopologyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TopologyError::NoDreadySource => write!(f, "no ADC generates DREADY"),
+            TopologyError::MultipleDreadySources => write!(f, "more than one ADC generates DREADY"),
+            TopologyError::AdcFreqMismatch => write!(f, "adc_freq differs between the ADCs"),
+            TopologyError::BwMismatch => write!(f, "bw differs between the ADCs"),
+            TopologyError::InvalidIndex(i) => write!(f, "invalid ADC index {i}"),
+            TopologyError::DreadyMismatch(i) => {
+                write!(
+                    f,
+                    "ADC {i} is expected to generate DREADY but has clkout_en set"
+                )
+            }
+            TopologyError::ClkoutDisabled(i) => {
+                write!(
+                    f,
+                    "ADC {i} is expected to feed CLKOUT but has clkout_en cleared"
+                )
+            }
+            TopologyError::DreadyClkout(i) => {
+                write!(f, "ADC {i} generates DREADY and cannot feed CLKOUT")
+            }
+            TopologyError::ClockLoop(i) => write!(f, "clock chain of ADC {i} loops"),
+        }
+    }
+}
+
+impl core::error::Error for TopologyError {}
+
 impl<SPI, CS, S, P, const N: usize> Ade791x<SPI, CS, N>
 where
     SPI: spi::Transfer<u8, Error = S>,
@@ -17,21 +197,98 @@ where
     /// # Arguments
     /// * `spi` - The SPI interface implementing the [`spi::Transfer`] trait.
     /// * `adcs` - The array of tuples containing the CS output pins (implementing the
-    /// [`OutputPin`]) trait and the chips as [`Chip`].
+    ///   [`OutputPin`]) trait and the chips as [`Chip`].
     pub fn new(spi: SPI, adcs: [(CS, Chip); N]) -> Self {
         Self {
             spi,
-            adcs: adcs.map(|(cs, chip)| ade791x::Ade791x::new(cs, chip)),
+            dready: NoDready,
+            adc_freq: AdcFreqVal::default(),
+            adcs: {
+                let mut index = 0;
+                adcs.map(|(cs, chip)| {
+                    index += 1;
+                    ade791x::Ade791x::new(cs, chip, index - 1)
+                })
+            },
+            topology: Topology {
+                clock: [ClockSource::Crystal; N],
+                dready: 0,
+            },
+            explicit_topology: false,
+            protection: true,
+            strict_emi_ctrl: false,
+            tracker: tracking::SampleTracker::new(AdcFreqVal::default()),
         }
     }
 
+    /// Gives the DREADY input pin to the driver, enabling [`Self::next_measurement()`] and
+    /// [`Self::collect()`]. The pin must be connected to the CLKOUT/DREADY pin of the ADC generating
+    /// the DREADY signal.
+    /// # Arguments
+    /// * `dready` - The DREADY input pin implementing the [`InputPin`] trait. Its error type may
+    ///   differ from the one of the CS pins, as long as it converts into it, and its errors are
+    ///   reported as [`Error::PinError`].
+    pub fn with_dready<DR>(self, dready: DR) -> Ade791x<SPI, CS, N, DR>
+    where
+        DR: InputPin,
+        P: From<DR::Error>,
+    {
+        Ade791x {
+            spi: self.spi,
+            dready,
+            adc_freq: self.adc_freq,
+            adcs: self.adcs,
+            topology: self.topology,
+            explicit_topology: self.explicit_topology,
+            protection: self.protection,
+            strict_emi_ctrl: self.strict_emi_ctrl,
+            tracker: self.tracker,
+        }
+    }
+}
+
+impl<SPI, CS, DR, S, P, const N: usize> Ade791x<SPI, CS, N, DR>
+where
+    SPI: spi::Transfer<u8, Error = S>,
+    CS: OutputPin<Error = P>,
+{
+    /// Sets the topology of the polyphase metering system, describing how the clock is distributed
+    /// between the ADCs and which ADC generates the DREADY signal. The topology is checked against
+    /// the configurations during [`Self::init()`]. If no topology is set, it is inferred from the
+    /// configurations, chaining the ADCs in CLKOUT mode in index order, followed by the ADC
+    /// generating the DREADY signal. The structure of the topology is checked immediately,
+    /// returning a [`TopologyError`] without applying it if the indexes are out of range, if the ADC
+    /// generating the DREADY signal provides the clock to other ADCs or if a clock chain loops.
+    /// # Arguments
+    /// * `topology` - The [`Topology`] of the polyphase metering system.
+    pub fn set_topology(&mut self, topology: Topology<N>) -> Result<(), TopologyError> {
+        topology.check()?;
+        self.topology = topology;
+        self.explicit_topology = true;
+        Ok(())
+    }
+
+    /// Returns the [`Topology`] of the polyphase metering system, as set with
+    /// [`Self::set_topology()`] or inferred during [`Self::init()`].
+    pub fn topology(&self) -> Topology<N> {
+        self.topology
+    }
+
     /// Initializes the ADCs, applying the given configurations. After this method, the ADCs are
-    /// ready to use.
+    /// ready to use. The EMI settings are checked before initializing the ADCs: every ADC must have
+    /// at least one slot, otherwise a [`Error::EmiCtrlEmpty`] is returned. With the strict check
+    /// enabled, see [`Self::set_strict_emi_ctrl()`], the slots must not overlap as well, otherwise
+    /// a [`Error::EmiCtrlOverlap`] is returned. Use [`Self::recommended_emi_ctrl()`] to get a
+    /// non-overlapping assignment. With more than one ADC, the
+    /// topology is checked as well, returning a [`Error::Topology`] if it is not valid: exactly one
+    /// ADC must generate the DREADY signal while the others are in CLKOUT mode, all the ADCs must
+    /// share the same `adc_freq` and `bw` settings, and the clock chain described by the
+    /// [`Topology`] must match the configurations.
     /// # Arguments
     /// * `delay` - The delay source implementing the [`DelayMs`] trait.
     /// * `config` - An array of [`Config`] structs containing the configurations for the ADCs.
     /// * `calibration` - An array of [`Calibration`] structs containing the calibration values for
-    /// the ADCs.
+    ///   the ADCs.
     /// * `emi_ctrl` - An array of [`EmiCtrl`] structs containing the EMI settings for the ADCs.
     pub fn init(
         &mut self,
@@ -40,28 +297,116 @@ where
         calibration: [Calibration; N],
         emi_ctrl: [EmiCtrl; N],
     ) -> Result<(), Error<S, P>> {
-        for i in 0..N {
-            self.adcs[i].init(&mut self.spi, delay, config[i], calibration[i], emi_ctrl[i])?;
-        }
+        self.validate_emi_ctrl(&emi_ctrl)?;
         if N > 1 {
-            self.sync()?;
+            self.validate_topology(&config).map_err(Error::Topology)?;
         }
-        self.lock()?;
-        Ok(())
+        let result = self.init_adcs(delay, config, calibration, emi_ctrl);
+        if let Err(e) = result {
+            if self.protection {
+                self.lock().ok();
+            }
+            return Err(e);
+        }
+        self.relock()
+    }
+
+    /// Enables or disables the protection of the internal registers of the ADCs. With the
+    /// protection enabled, which is the default, the registers are locked at the end of
+    /// [`Self::init()`], of every [`Session`] and of every CONFIG writing of the temperature
+    /// schedule, and the lock is verified through the `ic_prot` bit of the STATUS0 register. With
+    /// the protection disabled, e.g. during development, the registers are left unlocked after the
+    /// next [`Self::init()`] or [`Session`].
+    /// # Arguments
+    /// * `enabled` - `true` to lock the registers, `false` to leave them unlocked.
+    pub fn set_protection(&mut self, enabled: bool) {
+        self.protection = enabled;
+        for adc in &mut self.adcs {
+            adc.set_protection(enabled);
+        }
+    }
+
+    /// Returns `true` if the protection of the internal registers is enabled, `false` otherwise.
+    /// See [`Self::set_protection()`].
+    pub fn protection(&self) -> bool {
+        self.protection
+    }
+
+    /// Enables or disables the strict check of the EMI settings. With the strict check enabled,
+    /// [`Self::init()`] and [`Session::write_emi_ctrl()`] reject the assignments where, with up to
+    /// 8 ADCs, a slot is shared between ADCs. The check is disabled by default, as sharing the
+    /// slots is allowed by the datasheet, e.g. with the default [`EmiCtrl`] settings.
+    /// # Arguments
+    /// * `enabled` - `true` to reject the overlapping slots, `false` to accept them.
+    pub fn set_strict_emi_ctrl(&mut self, enabled: bool) {
+        self.strict_emi_ctrl = enabled;
+    }
+
+    /// Returns `true` if the strict check of the EMI settings is enabled, `false` otherwise. See
+    /// [`Self::set_strict_emi_ctrl()`].
+    pub fn strict_emi_ctrl(&self) -> bool {
+        self.strict_emi_ctrl
+    }
+
+    /// Unlocks the internal registers of the ADCs, returning a [`Session`] guard that allows
+    /// several register writings. The registers are locked again and the lock is verified with
+    /// [`Session::finish()`], or locked on a best-effort basis when the guard is dropped, e.g.
+    /// because of an early return on error. Prefer [`Self::configure()`], which always finishes the
+    /// session.
+    pub fn session(&mut self) -> Result<Session<'_, SPI, CS, N, DR>, Error<S, P>> {
+        if let Err(e) = self.unlock() {
+            if self.protection {
+                self.lock().ok();
+            }
+            return Err(e);
+        }
+        Ok(Session {
+            adc: self,
+            finished: false,
+        })
+    }
+
+    /// Runs the given closure in a [`Session`], unlocking the internal registers of the ADCs once
+    /// for all the writings performed by the closure. The registers are always locked again and the
+    /// lock is verified, even if the closure returns an error, in which case that error is
+    /// returned. If the lock cannot be verified, a [`Error::LockFailed`] is returned.
+    /// # Arguments
+    /// * `f` - The closure performing the writings through the [`Session`].
+    ///
+    /// ```rust ignore
+    /// adc.configure(|session| {
+    ///     session.write_emi_ctrl(emi_ctrl)?;
+    ///     session.adjust_sync()
+    /// })?;
+    /// ```
+    pub fn configure<T, F>(&mut self, f: F) -> Result<T, Error<S, P>>
+    where
+        F: FnOnce(&mut Session<'_, SPI, CS, N, DR>) -> Result<T, Error<S, P>>,
+    {
+        let mut session = self.session()?;
+        match f(&mut session) {
+            Ok(x) => {
+                session.finish()?;
+                Ok(x)
+            }
+            Err(e) => {
+                session.finish().ok();
+                Err(e)
+            }
+        }
+    }
+
+    /// Returns the recommended [`EmiCtrl`] assignment for the ADCs, spreading the isolated dc-to-dc
+    /// converters evenly across the 8 slots of the CLKIN/4 clock. See [`EmiCtrl::allocate()`].
+    pub fn recommended_emi_ctrl(&self) -> [EmiCtrl; N] {
+        EmiCtrl::allocate(self.chips())
     }
 
     /// Performs a hardware reset of the ADCs. During a hardware reset, all the registers are set to
     /// their default values and the dc-to-dc converters are shut down. After a hardware reset, the
     /// ADCs need to be initialized again, using [`Self::init()`].
     pub fn hard_reset(&mut self) -> Result<(), Error<S, P>> {
-        for adc in &mut self.adcs[1.min(N)..] {
-            adc.broadcast_listen()?;
-        }
-        self.adcs[0].hard_reset(&mut self.spi)?;
-        for adc in &mut self.adcs[1.min(N)..] {
-            adc.broadcast_end()?;
-        }
-        Ok(())
+        self.broadcast(ade791x::Ade791x::hard_reset)
     }
 
     /// Performs a software reset of the ADCs. During a software reset, all the internal registers
@@ -79,53 +424,65 @@ where
     /// modulators. Although the ADE7912/ADE7913 configuration registers maintain their values, the
     /// `iwv`, `v1wv`, and `v2wv` [`Measurement`] fields are in an undefined state.
     pub fn powerdown(&mut self) -> Result<(), Error<S, P>> {
-        self.unlock()?;
-        for adc in &mut self.adcs {
-            adc.powerdown(&mut self.spi)?;
-        }
-        self.lock()?;
-        Ok(())
+        self.configure(|session| session.powerdown())
     }
 
     /// Wakes-up the ADCs by turning on the dc-to-dc converters and activating the Σ-Δ modulators.
     pub fn wakeup(&mut self) -> Result<(), Error<S, P>> {
-        self.unlock()?;
-        for adc in &mut self.adcs {
-            adc.wakeup(&mut self.spi)?;
-        }
-        self.lock()?;
-        Ok(())
+        self.configure(|session| session.wakeup())
+    }
+
+    /// Powers-down the ADC at the given index, leaving the others running, e.g. to save the power
+    /// of the isolated supply of a disconnected phase. The ADC generating the DREADY signal, and an
+    /// ADC providing the clock to an ADC that is not powered-down, cannot be powered-down on their
+    /// own, as the powerdown forces `clkout_en = false`: a [`Error::PowerdownNotPermitted`] is
+    /// returned. The DREADY signal is not handed over to another ADC, as only the CLKOUT/DREADY pin
+    /// wired to the host can provide it.
+    ///
+    /// Only the leaves of the clock tree can therefore be powered-down, i.e. the ADCs that do not
+    /// generate DREADY and do not clock a running ADC. The daisy chain inferred by [`Self::init()`]
+    /// has no such ADC, as every ADC either clocks the next one or generates DREADY: set a
+    /// [`Topology`] with [`Self::set_topology()`] before the initialization, e.g. a star where the
+    /// first ADC clocks all the others and the last one generates DREADY, to power-down the ADCs in
+    /// between. The ADC is not read until it is woken-up with [`Self::wakeup_device()`].
+    /// # Arguments
+    /// * `index` - The index of the ADC to power-down.
+    pub fn powerdown_device(&mut self, index: usize) -> Result<(), Error<S, P>> {
+        self.configure(|session| session.powerdown_device(index))
+    }
+
+    /// Wakes-up the ADC at the given index, powered-down with [`Self::powerdown_device()`], and
+    /// synchronizes its internal counter with the ADC generating the DREADY signal. If the ADC
+    /// providing its clock is powered-down, a [`Error::WakeupNotPermitted`] is returned. Returns the
+    /// drift of the counter before the synchronization, as in [`Self::adjust_sync()`].
+    /// # Arguments
+    /// * `index` - The index of the ADC to wake-up.
+    pub fn wakeup_device(&mut self, index: usize) -> Result<i16, Error<S, P>> {
+        self.configure(|session| session.wakeup_device(index))
+    }
+
+    /// Returns `true` for each ADC powered-down with [`Self::powerdown()`] or
+    /// [`Self::powerdown_device()`], `false` otherwise.
+    pub fn powered_down(&self) -> [bool; N] {
+        core::array::from_fn(|i| self.adcs[i].is_powered_down())
     }
 
     /// Adjusts the synchronization of the ADCs internal counters by following the procedure
     /// described in the ADE7912/ADE7913 Datasheet. The method compares the value of the counter of
-    /// the reference ADC (i.e. the one that is generating the DREADY signal) with the values of the
-    /// counters of the other ADCs, adjusting the ADCs that are out of sync.
+    /// the reference ADC (i.e. the one that is generating the DREADY signal, according to the
+    /// [`Topology`]) with the values of the counters of the other ADCs, adjusting the ADCs that are
+    /// out of sync. The ADCs powered-down with [`Self::powerdown_device()`] are skipped and their
+    /// drift is reported as zero.
     pub fn adjust_sync(&mut self) -> Result<[i16; N], Error<S, P>> {
-        self.unlock()?;
-        self.snap()?;
-        let ref_adc_index = self
-            .adcs
-            .iter()
-            .position(|adc| adc.is_dr_source())
-            .unwrap_or(0);
-        let cref = self.adcs[ref_adc_index].get_cnt_snapshot(&mut self.spi)?;
-        let mut drift = [0; N];
-        for (i, val) in drift.iter_mut().enumerate() {
-            if i == ref_adc_index {
-                continue;
-            }
-            *val = self.adcs[i].adjust_sync(&mut self.spi, cref)?;
-        }
-        self.lock()?;
-        Ok(drift)
+        self.configure(|session| session.adjust_sync())
     }
 
     /// Returns the latest available measurement from the ADCs as an array of [`RawMeasurement`]
     /// structs. Call this method inside the ISR from the DREADY pin to get a new measurement as
     /// soon as it's ready. This method does not convert the received data. To get converted
-    /// metrics, use [`Self::get_measurement()`] instead. This method does not perform CRC checks on
-    /// received data.
+    /// metrics, use [`Self::get_measurement()`] instead. CRC checks on received data are performed
+    /// only if enabled with [`Self::set_channels()`]. The ADCs powered-down with
+    /// [`Self::powerdown_device()`] are not read and their fields are set to zero.
     pub fn get_raw_measurement(&mut self) -> Result<[RawMeasurement; N], Error<S, P>> {
         let mut raw_measurement = [RawMeasurement {
             iwv: 0,
@@ -133,6 +490,9 @@ where
             v2wv: 0,
         }; N];
         for (i, val) in raw_measurement.iter_mut().enumerate() {
+            if self.adcs[i].is_powered_down() {
+                continue;
+            }
             *val = self.adcs[i].get_raw_measurement(&mut self.spi)?;
         }
         Ok(raw_measurement)
@@ -141,7 +501,9 @@ where
     /// Returns the latest available measurement from the ADCs as an array of [`Measurement`]
     /// structs. Call this method inside the ISR from the DREADY pin to get a new measurement as
     /// soon as it's ready. This method converts raw data to voltage and current measurements using
-    /// the provided calibration values. This method does not perform CRC checks on received data.
+    /// the provided calibration values. CRC checks on received data are performed only if enabled
+    /// with [`Self::set_channels()`]. The ADCs powered-down with [`Self::powerdown_device()`] are
+    /// not read and their fields are set to zero.
     pub fn get_measurement(&mut self) -> Result<[Measurement; N], Error<S, P>> {
         let mut measurement = [Measurement {
             current: 0.0,
@@ -149,60 +511,639 @@ where
             aux: MeasurementAux::Voltage(0.0),
         }; N];
         for (i, val) in measurement.iter_mut().enumerate() {
+            if self.adcs[i].is_powered_down() {
+                continue;
+            }
             *val = self.adcs[i].get_measurement(&mut self.spi)?;
         }
         Ok(measurement)
     }
 
+    /// Returns the latest available measurement from the ADCs as an array of [`Measurement`]
+    /// structs, stamped with its sample index. Missed samples and duplicate reads are detected by
+    /// comparing the given timestamp with the one of the previous read, based on the ADC output
+    /// frequency set during [`Self::init()`]. See [`tracking::SampleTracker`].
+    /// # Arguments
+    /// * `timestamp_us` - The timestamp of the read in µs, taken from a monotonic clock.
+    pub fn get_tracked_measurement(
+        &mut self,
+        timestamp_us: u64,
+    ) -> Result<tracking::Tracked<[Measurement; N]>, Error<S, P>> {
+        let raw_measurement = self.get_raw_measurement()?;
+        let tracked = self.tracker.track(timestamp_us, raw_measurement);
+        let mut measurement = [Measurement {
+            current: 0.0,
+            voltage: 0.0,
+            aux: MeasurementAux::Voltage(0.0),
+        }; N];
+        for (i, val) in measurement.iter_mut().enumerate() {
+            if self.adcs[i].is_powered_down() {
+                continue;
+            }
+            *val = self.adcs[i].convert(&mut self.spi, &raw_measurement[i])?;
+        }
+        Ok(tracked.map(|_| measurement))
+    }
+
+    /// Returns the [`record::Header`] describing the ADCs, to start a recording of their samples
+    /// with a [`record::Recorder`]. See the [`record`] module.
+    /// # Arguments
+    /// * `status` - `true` to record the ADC CRC, the STATUS0 register and the counter snapshot
+    ///   alongside the waveforms, `false` otherwise.
+    /// * `delta` - `true` to delta compress the waveforms, `false` otherwise.
+    pub fn recording_header(&self, status: bool, delta: bool) -> record::Header<N> {
+        record::Header {
+            chip: self.chips(),
+            config: core::array::from_fn(|i| self.adcs[i].config()),
+            emi_ctrl: core::array::from_fn(|i| self.adcs[i].emi_ctrl()),
+            channels: self.channels(),
+            calibration: core::array::from_fn(|i| self.adcs[i].resolved_calibration()),
+            status,
+            delta,
+        }
+    }
+
+    /// Reads the latest available measurement from the ADCs and records it in the given
+    /// [`capture::Capture`], returning the state of the capture after the sample is recorded. Call
+    /// this method inside the ISR from the DREADY pin. See the [`capture`] module.
+    /// # Arguments
+    /// * `capture` - The [`capture::Capture`] recording the samples.
+    pub fn capture<const L: usize>(
+        &mut self,
+        capture: &mut capture::Capture<N, L>,
+    ) -> Result<capture::CaptureState, Error<S, P>> {
+        let raw_measurement = self.get_raw_measurement()?;
+        Ok(capture.push(&raw_measurement))
+    }
+
+    /// Returns the [`tracking::SampleTracker`] used by [`Self::get_tracked_measurement()`], holding
+    /// the counters of missed samples and duplicate reads.
+    pub fn tracker(&self) -> &tracking::SampleTracker {
+        &self.tracker
+    }
+
+    /// Returns the temperature of the dies in °C, averaged over the given number of samples. A new
+    /// sample is read every millisecond, so that consecutive readings never refer to the same ADC
+    /// output. The temperature is [`None`] for the ADCs whose auxiliary channel is not measuring
+    /// the temperature, i.e. the ADE7913 with `temp_en = false` in [`Config`].
+    /// # Arguments
+    /// * `delay` - The delay source implementing the [`DelayMs`] trait.
+    /// * `samples` - The number of samples to average.
+    pub fn read_temperature(
+        &mut self,
+        delay: &mut dyn DelayMs<u32>,
+        samples: u32,
+    ) -> Result<[Option<f32>; N], Error<S, P>> {
+        let samples = samples.max(1);
+        let mut sum = [0_i64; N];
+        for n in 0..samples {
+            if n > 0 {
+                delay.delay_ms(1);
+            }
+            for (i, val) in sum.iter_mut().enumerate() {
+                if self.adcs[i].is_temperature() {
+                    *val += self.adcs[i].get_raw_aux(&mut self.spi)? as i64;
+                }
+            }
+        }
+        Ok(core::array::from_fn(|i| {
+            self.adcs[i].is_temperature().then(|| {
+                let coefficients = self.adcs[i].conversion().aux;
+                coefficients.scale * (sum[i] as f64 / samples as f64) as f32 + coefficients.offset
+            })
+        }))
+    }
+
+    /// Sets the schedule of the interleaved temperature sampling for each ADC. When a schedule is
+    /// active, [`Self::get_measurement()`] periodically switches the second voltage channel of the
+    /// ADC to the internal temperature sensor, discarding the settling samples, and then restores
+    /// the voltage measurement. During the switch, the auxiliary field of [`Measurement`] is set to
+    /// [`MeasurementAux::Unavailable`], and the measured temperatures can be retrieved using
+    /// [`Self::take_temperature()`]. The schedule is only permitted for the ADE7913 with
+    /// `temp_en = false` in [`Config`], with the second voltage channel read as selected with
+    /// [`Self::set_channels()`]. The schedule only advances in [`Self::get_measurement()`] and
+    /// [`Self::get_tracked_measurement()`]: the raw measurements, the samples and the
+    /// [`split::SampleReader`] do not advance it, leaving the channel in its current mode.
+    /// # Arguments
+    /// * `schedule` - An array of [`TemperatureSchedule`] structs to follow for the ADCs, or
+    ///   [`None`] to disable it.
+    pub fn set_temperature_schedule(
+        &mut self,
+        schedule: [Option<TemperatureSchedule>; N],
+    ) -> Result<(), Error<S, P>> {
+        for (adc, schedule) in self.adcs.iter_mut().zip(schedule) {
+            adc.set_temperature_schedule(&mut self.spi, schedule)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the latest temperatures in °C measured following the schedules set with
+    /// [`Self::set_temperature_schedule()`], if new ones are available.
+    pub fn take_temperature(&mut self) -> [Option<f32>; N] {
+        core::array::from_fn(|i| self.adcs[i].take_temperature())
+    }
+
+    /// Sets new calibration values for the ADCs, updating the precomputed conversion coefficients
+    /// used by [`Self::get_measurement()`]. Calibration values set to [`None`] are resolved as in
+    /// [`Self::init()`].
+    /// # Arguments
+    /// * `calibration` - An array of [`Calibration`] structs containing the calibration values for
+    ///   the ADCs.
+    pub fn set_calibration(&mut self, calibration: [Calibration; N]) {
+        for (adc, calibration) in self.adcs.iter_mut().zip(calibration) {
+            adc.set_calibration(calibration);
+        }
+    }
+
+    /// Returns the precomputed [`Conversion`] structs used by [`Self::get_measurement()`] to
+    /// convert raw data.
+    pub fn conversion(&self) -> [Conversion; N] {
+        core::array::from_fn(|i| self.adcs[i].conversion())
+    }
+
+    /// Selects the channels read by [`Self::get_raw_measurement()`] and
+    /// [`Self::get_measurement()`] for each ADC. Reading fewer channels shortens the burst
+    /// transactions, reducing the SPI time spent in the DREADY ISR. The fields corresponding to the
+    /// channels that are not read are set to zero in [`RawMeasurement`], while in [`Measurement`]
+    /// the voltage is set to NaN and the auxiliary field to [`MeasurementAux::Unavailable`]. The
+    /// second voltage channel must be read from the ADCs with an active
+    /// interleaved temperature sampling, otherwise a [`Error::TemperatureScheduleNotPermitted`] is
+    /// returned and no selection is changed.
+    /// # Arguments
+    /// * `channels` - An array of [`Channels`] values containing the set of channels to read for
+    ///   the ADCs.
+    pub fn set_channels(&mut self, channels: [Channels; N]) -> Result<(), Error<S, P>> {
+        for (adc, channels) in self.adcs.iter().zip(channels) {
+            adc.check_channels(channels)?;
+        }
+        for (adc, channels) in self.adcs.iter_mut().zip(channels) {
+            adc.set_channels(channels);
+        }
+        Ok(())
+    }
+
+    /// Returns the channels read from each ADC, as selected with [`Self::set_channels()`].
+    pub fn channels(&self) -> [Channels; N] {
+        core::array::from_fn(|i| self.adcs[i].channels())
+    }
+
+    /// Sets the [`RetryPolicy`] applied to the SPI transactions of all the ADCs. The checked
+    /// register writings of [`Self::init()`] are retried with the backoff of the policy, while the
+    /// burst reads of the measurements and samples are retried immediately.
+    /// # Arguments
+    /// * `retry` - The [`RetryPolicy`] struct to apply.
+    pub fn set_retry_policy(&mut self, retry: RetryPolicy) {
+        for adc in self.adcs.iter_mut() {
+            adc.set_retry_policy(retry);
+        }
+    }
+
+    /// Returns the [`RetryPolicy`] applied to the SPI transactions, as set with
+    /// [`Self::set_retry_policy()`].
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.adcs
+            .first()
+            .map_or(RetryPolicy::default(), |adc| adc.retry_policy())
+    }
+
+    /// Returns the [`RetryCounters`] of each ADC.
+    pub fn retry_counters(&self) -> [RetryCounters; N] {
+        core::array::from_fn(|i| self.adcs[i].retry_counters())
+    }
+
+    /// Resets the [`RetryCounters`] of all the ADCs to zero.
+    pub fn reset_retry_counters(&mut self) {
+        for adc in self.adcs.iter_mut() {
+            adc.reset_retry_counters();
+        }
+    }
+
+    /// Returns the chip of each ADC as a [`Chip`].
+    pub fn chips(&self) -> [Chip; N] {
+        core::array::from_fn(|i| self.adcs[i].chip())
+    }
+
+    /// Returns the ADC output frequency in Hz, as set during [`Self::init()`].
+    pub fn sample_rate(&self) -> u32 {
+        self.adc_freq.hz()
+    }
+
+    /// Returns the latest available sample from the ADCs as an array of [`Sample`] structs. Each
+    /// sample is read in a single burst transaction and contains, alongside the raw waveforms, the
+    /// ADC CRC, the STATUS0 register and the counter snapshot. Use this method instead of
+    /// [`Self::get_raw_measurement()`] to perform integrity, reset and timing checks without
+    /// additional SPI traffic.
+    pub fn get_sample(&mut self) -> Result<[Sample; N], Error<S, P>> {
+        let mut sample = [Sample {
+            raw: RawMeasurement {
+                iwv: 0,
+                v1wv: 0,
+                v2wv: 0,
+            },
+            adc_crc: 0,
+            status0: Status0::default(),
+            cnt_snapshot: 0,
+        }; N];
+        for (i, val) in sample.iter_mut().enumerate() {
+            if self.adcs[i].is_powered_down() {
+                continue;
+            }
+            *val = self.adcs[i].get_sample(&mut self.spi)?;
+        }
+        Ok(sample)
+    }
+
+    /// Reads every readable register of each ADC into an array of [`dump::RegisterDump`] structs.
+    pub fn dump_registers(&mut self) -> Result<[dump::RegisterDump; N], Error<S, P>> {
+        let mut dump = [dump::RegisterDump::default(); N];
+        for (i, val) in dump.iter_mut().enumerate() {
+            *val = self.adcs[i].dump_registers(&mut self.spi)?;
+        }
+        Ok(dump)
+    }
+
+    /// Returns the expected content of the configuration and status registers of each ADC, derived
+    /// from the configurations given to [`Self::init()`], as an array of [`dump::Expected`]
+    /// structs. The registers are expected to be protected, as the driver locks them after every
+    /// operation, unless the protection is disabled with [`Self::set_protection()`].
+    pub fn expected_registers(&self) -> [dump::Expected; N] {
+        core::array::from_fn(|i| self.adcs[i].expected_registers(self.protection))
+    }
+
+    /// Dumps the registers of each ADC and compares them with the expected content, returning the
+    /// differences as an array of [`dump::RegisterDiff`] structs. See [`Self::dump_registers()`]
+    /// and [`Self::expected_registers()`].
+    pub fn diff_registers(&mut self) -> Result<[dump::RegisterDiff; N], Error<S, P>> {
+        let dump = self.dump_registers()?;
+        let expected = self.expected_registers();
+        Ok(core::array::from_fn(|i| dump[i].diff(&expected[i])))
+    }
+
+    /// Splits the driver into a [`split::SampleReader`], to be used inside the ISR from the DREADY
+    /// pin, and a [`split::Controller`], to be used from a lower-priority task, coordinated through
+    /// the given [`split::Shared`] struct without any lock. See the [`split`] module.
+    /// # Arguments
+    /// * `shared` - The [`split::Shared`] struct containing the ring buffer and the mailbox.
+    /// # Panics
+    /// Panics if the [`split::Shared`] struct was already used by another split.
+    pub fn split<const L: usize>(
+        self,
+        shared: &split::Shared<N, L>,
+    ) -> (
+        split::SampleReader<'_, SPI, CS, N, L, DR>,
+        split::Controller<'_, N, L>,
+    ) {
+        assert!(
+            shared.claim(),
+            "the shared struct was already used by another split"
+        );
+        let conversion = self.conversion();
+        (
+            split::SampleReader::new(self, shared),
+            split::Controller::new(shared, conversion),
+        )
+    }
+
+    /// Initializes the ADCs after the checks of [`Self::init()`], leaving the registers unlocked.
+    /// # Arguments
+    /// * `delay` - The delay source implementing the [`DelayMs`] trait.
+    /// * `config` - An array of [`Config`] structs containing the configurations for the ADCs.
+    /// * `calibration` - An array of [`Calibration`] structs containing the calibration values for
+    ///   the ADCs.
+    /// * `emi_ctrl` - An array of [`EmiCtrl`] structs containing the EMI settings for the ADCs.
+    fn init_adcs(
+        &mut self,
+        delay: &mut dyn DelayMs<u32>,
+        config: [Config; N],
+        calibration: [Calibration; N],
+        emi_ctrl: [EmiCtrl; N],
+    ) -> Result<(), Error<S, P>> {
+        for i in 0..N {
+            self.adcs[i].init(&mut self.spi, delay, config[i], calibration[i], emi_ctrl[i])?;
+        }
+        if N > 1 {
+            self.sync()?;
+        }
+        if N > 0 {
+            self.adc_freq = config[0].adc_freq;
+            self.tracker.reset(self.adc_freq);
+        }
+        Ok(())
+    }
+
+    /// Checks the EMI settings of the ADCs, strictly if enabled with [`Self::set_strict_emi_ctrl()`].
+    /// # Arguments
+    /// * `emi_ctrl` - An array of [`EmiCtrl`] structs containing the EMI settings for the ADCs.
+    fn validate_emi_ctrl(&self, emi_ctrl: &[EmiCtrl; N]) -> Result<(), Error<S, P>> {
+        if self.strict_emi_ctrl {
+            EmiCtrl::validate_strict(emi_ctrl)
+        } else {
+            EmiCtrl::validate(emi_ctrl)
+        }
+    }
+
+    /// Checks the topology of the polyphase metering system given the configurations of the ADCs.
+    /// If no topology was set, it is inferred from the configurations.
+    /// # Arguments
+    /// * `config` - An array of [`Config`] structs containing the configurations for the ADCs.
+    fn validate_topology(&mut self, config: &[Config; N]) -> Result<(), TopologyError> {
+        let mut dready = None;
+        for (i, val) in config.iter().enumerate() {
+            if !val.clkout_en && dready.replace(i).is_some() {
+                return Err(TopologyError::MultipleDreadySources);
+            }
+            if val.adc_freq != config[0].adc_freq {
+                return Err(TopologyError::AdcFreqMismatch);
+            }
+            if val.bw != config[0].bw {
+                return Err(TopologyError::BwMismatch);
+            }
+        }
+        dready.ok_or(TopologyError::NoDreadySource)?;
+        if !self.explicit_topology {
+            self.topology = Topology::infer(config);
+        }
+        self.topology.validate(config)
+    }
+
     /// Performs the synchronization procedure for the ADCs. After this procedure, the internal
     /// counters of the ADCs are aligned. This method should be called only during initialization,
     /// as it invalidates the readings for some ADC cycles. For aligning the counters during
     /// operation, use [`Self::adjust_sync()`].
     fn sync(&mut self) -> Result<(), Error<S, P>> {
-        for adc in &mut self.adcs[1.min(N)..] {
-            adc.broadcast_listen()?;
-        }
-        self.adcs[0].sync(&mut self.spi)?;
-        for adc in &mut self.adcs[1.min(N)..] {
-            adc.broadcast_end()?;
-        }
-        Ok(())
+        self.broadcast(ade791x::Ade791x::sync)
     }
 
     /// Performs the snap procedure for the ADCs. After this procedure, the values of the internal
     /// internal counters of the ADCs is captured at the same time and stored in the CNT_SNAPSHOT
     /// register, that can be read using [`Self::get_measurement()`].
     fn snap(&mut self) -> Result<(), Error<S, P>> {
-        for adc in &mut self.adcs[1.min(N)..] {
-            adc.broadcast_listen()?;
+        self.broadcast(ade791x::Ade791x::snap)
+    }
+
+    /// Locks the internal register of the ADCs, meaning that they cannot be written.
+    fn lock(&mut self) -> Result<(), Error<S, P>> {
+        self.broadcast(ade791x::Ade791x::lock)
+    }
+
+    /// Unlocks the internal register of the ADC, meaning that they can be written.
+    fn unlock(&mut self) -> Result<(), Error<S, P>> {
+        self.broadcast(ade791x::Ade791x::unlock)
+    }
+
+    /// Locks the internal registers of the ADCs and verifies the `ic_prot` bit of their STATUS0
+    /// register, returning a [`Error::LockFailed`] if it is cleared. Does nothing if the protection
+    /// is disabled.
+    fn relock(&mut self) -> Result<(), Error<S, P>> {
+        if !self.protection {
+            return Ok(());
         }
-        self.adcs[0].snap(&mut self.spi)?;
-        for adc in &mut self.adcs[1.min(N)..] {
-            adc.broadcast_end()?;
+        self.lock()?;
+        for (i, adc) in self.adcs.iter_mut().enumerate() {
+            if !adc.is_locked(&mut self.spi)? {
+                return Err(Error::LockFailed { device: i });
+            }
         }
         Ok(())
     }
 
-    /// Locks the internal register of the ADCs, meaning that they cannot be written.
-    fn lock(&mut self) -> Result<(), Error<S, P>> {
-        for adc in &mut self.adcs[1.min(N)..] {
-            adc.broadcast_listen()?;
+    /// Performs a broadcast write operation, where the reference ADC (i.e. the one that is
+    /// generating the DREADY signal, according to the [`Topology`]) sends the command while the
+    /// other ADCs are listening on the SPI bus.
+    /// # Arguments
+    /// * `op` - The operation performed by the reference ADC.
+    fn broadcast<F>(&mut self, op: F) -> Result<(), Error<S, P>>
+    where
+        F: FnOnce(&mut ade791x::Ade791x<SPI, CS>, &mut SPI) -> Result<(), Error<S, P>>,
+    {
+        if N == 0 {
+            return Ok(());
+        }
+        let ref_adc_index = self.topology.dready;
+        for (i, adc) in self.adcs.iter_mut().enumerate() {
+            if i != ref_adc_index {
+                adc.broadcast_listen()?;
+            }
         }
-        self.adcs[0].lock(&mut self.spi)?;
-        for adc in &mut self.adcs[1.min(N)..] {
-            adc.broadcast_end()?;
+        op(&mut self.adcs[ref_adc_index], &mut self.spi)?;
+        for (i, adc) in self.adcs.iter_mut().enumerate() {
+            if i != ref_adc_index {
+                adc.broadcast_end()?;
+            }
         }
         Ok(())
     }
+}
 
-    /// Unlocks the internal register of the ADC, meaning that they can be written.
-    fn unlock(&mut self) -> Result<(), Error<S, P>> {
-        for adc in &mut self.adcs[1.min(N)..] {
-            adc.broadcast_listen()?;
+/// Represents a configuration session of a polyphase metering system, during which the internal
+/// registers of the ADCs are unlocked. The session is created with [`Ade791x::session()`] or
+/// [`Ade791x::configure()`], and must be closed with [`Self::finish()`], which locks the registers
+/// again and verifies the lock. If the session is dropped without being finished, the registers are
+/// locked on a best-effort basis, ignoring any error.
+pub struct Session<'a, SPI, CS, const N: usize, DR = NoDready>
+where
+    SPI: spi::Transfer<u8>,
+    CS: OutputPin,
+{
+    adc: &'a mut Ade791x<SPI, CS, N, DR>,
+    finished: bool,
+}
+
+impl<SPI, CS, DR, S, P, const N: usize> Session<'_, SPI, CS, N, DR>
+where
+    SPI: spi::Transfer<u8, Error = S>,
+    CS: OutputPin<Error = P>,
+{
+    /// Writes new configurations to the ADCs, updating the precomputed conversion coefficients.
+    /// With more than one ADC, the topology is checked as in [`Ade791x::init()`] and the ADCs are
+    /// synchronized again after the writings.
+    /// # Arguments
+    /// * `config` - An array of [`Config`] structs containing the configurations for the ADCs.
+    pub fn write_config(&mut self, config: [Config; N]) -> Result<(), Error<S, P>> {
+        let adc = &mut *self.adc;
+        if N > 1 {
+            adc.validate_topology(&config).map_err(Error::Topology)?;
+        }
+        for (i, config) in config.iter().enumerate() {
+            adc.adcs[i].write_config(&mut adc.spi, *config)?;
+        }
+        if N > 1 {
+            adc.sync()?;
+        }
+        if N > 0 {
+            adc.adc_freq = config[0].adc_freq;
+            adc.tracker.reset(adc.adc_freq);
+        }
+        Ok(())
+    }
+
+    /// Writes new EMI settings to the ADCs, checked as in [`Ade791x::init()`].
+    /// # Arguments
+    /// * `emi_ctrl` - An array of [`EmiCtrl`] structs containing the EMI settings for the ADCs.
+    pub fn write_emi_ctrl(&mut self, emi_ctrl: [EmiCtrl; N]) -> Result<(), Error<S, P>> {
+        self.adc.validate_emi_ctrl(&emi_ctrl)?;
+        let adc = &mut *self.adc;
+        for (i, emi_ctrl) in emi_ctrl.iter().enumerate() {
+            adc.adcs[i].write_emi_ctrl(&mut adc.spi, *emi_ctrl)?;
+        }
+        Ok(())
+    }
+
+    /// Powers-down the ADCs. See [`Ade791x::powerdown()`].
+    pub fn powerdown(&mut self) -> Result<(), Error<S, P>> {
+        let adc = &mut *self.adc;
+        for val in &mut adc.adcs {
+            val.powerdown(&mut adc.spi)?;
+        }
+        Ok(())
+    }
+
+    /// Wakes-up the ADCs. See [`Ade791x::wakeup()`].
+    pub fn wakeup(&mut self) -> Result<(), Error<S, P>> {
+        let adc = &mut *self.adc;
+        for val in &mut adc.adcs {
+            val.wakeup(&mut adc.spi)?;
+        }
+        Ok(())
+    }
+
+    /// Powers-down the ADC at the given index. See [`Ade791x::powerdown_device()`].
+    /// # Arguments
+    /// * `index` - The index of the ADC to power-down.
+    pub fn powerdown_device(&mut self, index: usize) -> Result<(), Error<S, P>> {
+        let adc = &mut *self.adc;
+        if index >= N {
+            return Err(Error::Topology(TopologyError::InvalidIndex(index)));
+        }
+        let feeds_powered = adc.topology.clock.iter().enumerate().any(|(i, clock)| {
+            *clock == ClockSource::Clkout(index) && !adc.adcs[i].is_powered_down()
+        });
+        if index == adc.topology.dready || feeds_powered {
+            return Err(Error::PowerdownNotPermitted { device: index });
         }
-        self.adcs[0].unlock(&mut self.spi)?;
-        for adc in &mut self.adcs[1.min(N)..] {
-            adc.broadcast_end()?;
+        adc.adcs[index].powerdown(&mut adc.spi)
+    }
+
+    /// Wakes-up the ADC at the given index and synchronizes it. See
+    /// [`Ade791x::wakeup_device()`].
+    /// # Arguments
+    /// * `index` - The index of the ADC to wake-up.
+    pub fn wakeup_device(&mut self, index: usize) -> Result<i16, Error<S, P>> {
+        let adc = &mut *self.adc;
+        if index >= N {
+            return Err(Error::Topology(TopologyError::InvalidIndex(index)));
+        }
+        if let ClockSource::Clkout(j) = adc.topology.clock[index] {
+            if adc.adcs[j].is_powered_down() {
+                return Err(Error::WakeupNotPermitted { device: index });
+            }
+        }
+        adc.adcs[index].wakeup(&mut adc.spi)?;
+        if index == adc.topology.dready {
+            return Ok(0);
+        }
+        adc.snap()?;
+        let cref = adc.adcs[adc.topology.dready].get_cnt_snapshot(&mut adc.spi)?;
+        adc.adcs[index].adjust_sync(&mut adc.spi, cref)
+    }
+
+    /// Adjusts the synchronization of the ADCs internal counters. See [`Ade791x::adjust_sync()`].
+    pub fn adjust_sync(&mut self) -> Result<[i16; N], Error<S, P>> {
+        let adc = &mut *self.adc;
+        adc.snap()?;
+        let ref_adc_index = adc.topology.dready;
+        let cref = adc.adcs[ref_adc_index].get_cnt_snapshot(&mut adc.spi)?;
+        let mut drift = [0; N];
+        for (i, val) in drift.iter_mut().enumerate() {
+            if i == ref_adc_index || adc.adcs[i].is_powered_down() {
+                continue;
+            }
+            *val = adc.adcs[i].adjust_sync(&mut adc.spi, cref)?;
+        }
+        Ok(drift)
+    }
+
+    /// Closes the session, locking the internal registers of the ADCs and verifying the lock. A
+    /// [`Error::LockFailed`] is returned if the registers are not protected. The registers are left
+    /// unlocked if the protection is disabled with [`Ade791x::set_protection()`].
+    pub fn finish(mut self) -> Result<(), Error<S, P>> {
+        self.finished = true;
+        self.adc.relock()
+    }
+}
+
+impl<SPI, CS, const N: usize, DR> Drop for Session<'_, SPI, CS, N, DR>
+where
+    SPI: spi::Transfer<u8>,
+    CS: OutputPin,
+{
+    fn drop(&mut self) {
+        if !self.finished && self.adc.protection {
+            self.adc.lock().ok();
+        }
+    }
+}
+
+impl<SPI, CS, DR, S, P, const N: usize> Ade791x<SPI, CS, N, DR>
+where
+    SPI: spi::Transfer<u8, Error = S>,
+    CS: OutputPin<Error = P>,
+    DR: InputPin,
+    P: From<DR::Error>,
+{
+    /// Waits for the next falling edge of the DREADY signal and returns the new measurement from the
+    /// ADCs as an array of [`Measurement`] structs. The DREADY line is polled every microsecond. If
+    /// it does not toggle within [`DREADY_TIMEOUT_PERIODS`] ADC output periods, the line is
+    /// considered stuck and [`Error::DreadyStuckLow`] or [`Error::DreadyStuckHigh`] is returned.
+    /// # Arguments
+    /// * `delay` - The delay source implementing the [`DelayUs`] trait.
+    pub fn next_measurement(
+        &mut self,
+        delay: &mut dyn DelayUs<u32>,
+    ) -> Result<[Measurement; N], Error<S, P>> {
+        self.wait_dready(delay)?;
+        self.get_measurement()
+    }
+
+    /// Fills the given buffer with consecutive measurements from the ADCs, waiting for the falling
+    /// edge of the DREADY signal before each read. See [`Self::next_measurement()`].
+    /// # Arguments
+    /// * `delay` - The delay source implementing the [`DelayUs`] trait.
+    /// * `buffer` - The buffer of arrays of [`Measurement`] structs to fill.
+    pub fn collect(
+        &mut self,
+        delay: &mut dyn DelayUs<u32>,
+        buffer: &mut [[Measurement; N]],
+    ) -> Result<(), Error<S, P>> {
+        for val in buffer.iter_mut() {
+            *val = self.next_measurement(delay)?;
+        }
+        Ok(())
+    }
+
+    /// Waits for the falling edge of the DREADY signal, that is for the line to be high and then
+    /// low, returning an error if the line does not toggle within the watchdog timeout.
+    /// # Arguments
+    /// * `delay` - The delay source implementing the [`DelayUs`] trait.
+    fn wait_dready(&mut self, delay: &mut dyn DelayUs<u32>) -> Result<(), Error<S, P>> {
+        let timeout_us = DREADY_TIMEOUT_PERIODS * 1_000_000 / self.adc_freq.hz();
+        for (high, error) in [
+            (true, Error::DreadyStuckLow),
+            (false, Error::DreadyStuckHigh),
+        ] {
+            let mut elapsed_us = 0;
+            while self
+                .dready
+                .is_high()
+                .map_err(|e| Error::PinError(e.into()))?
+                != high
+            {
+                if elapsed_us >= timeout_us {
+                    return Err(error);
+                }
+                delay.delay_us(1);
+                elapsed_us += 1;
+            }
         }
         Ok(())
     }
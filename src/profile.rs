@@ -0,0 +1,308 @@
+//! Binary format for persisting the configuration and calibration profile of the ADCs, e.g. in an
+//! EEPROM or in the internal flash of the microcontroller.
+//!
+//! A profile contains, for each ADC, the chip, the configuration, the EMI settings, the scaling of
+//! the sensors and the calibration values. The ADC records are length-prefixed, so that the format
+//! can evolve without breaking the profiles already stored: fields appended in later revisions
+//! take their default values when missing from an older profile, and unknown trailing bytes
+//! written by a newer revision are ignored. The version is only increased for changes that cannot
+//! be migrated this way. The profile ends with the CRC-16-CCITT of all the preceding bytes. All the
+//! values are little-endian.
+//!
+//! | Field          | Size          | Content                                                 |
+//! |----------------|---------------|---------------------------------------------------------|
+//! | Magic          | 4             | `ADEP`                                                  |
+//! | Version        | 1             | [`VERSION`]                                             |
+//! | ADC count      | 1             | `N`                                                     |
+//! | Payload length | 2             | Length of the ADC records                               |
+//! | ADC records    | `N` × (1 + …) | Record length, chip, CONFIG, EMI_CTRL, calibration      |
+//! |                |               | values and [`Scaling`]                                  |
+//! | CRC            | 2             | CRC-16-CCITT of the magic, header and ADC records       |
+//!
+#![cfg_attr(
+    feature = "embedded-storage",
+    doc = "With the `embedded-storage` feature, [`Profile::store()`] and [`Profile::load()`] write \
+           and read the profiles through the [`NorFlash`] and [`ReadNorFlash`] traits."
+)]
+#![cfg_attr(
+    not(feature = "embedded-storage"),
+    doc = "With the `embedded-storage` feature, `Profile::store()` and `Profile::load()` write and \
+           read the profiles through the `NorFlash` and `ReadNorFlash` traits."
+)]
+//!
+//! ```rust ignore
+//! let mut buf = [0; 512];
+//! let profile = profile::Profile::<3>::load(&mut flash, PROFILE_OFFSET, &mut buf).unwrap();
+//! adc.init(delay, profile.config, profile.calibration(), profile.emi_ctrl).unwrap();
+//! ```
+
+#[cfg(feature = "embedded-storage")]
+use embedded_storage::nor_flash::{NorFlash, ReadNorFlash};
+
+use super::codec::{self, CodecError, Reader, Writer};
+use super::*;
+
+/// Version of the profile format.
+pub const VERSION: u8 = 1;
+
+const MAGIC: &[u8; 4] = b"ADEP";
+const HEADER_LEN: usize = 8;
+const CRC_LEN: usize = 2;
+/// Maximum length of an ADC record.
+const RECORD_MAX_LEN: usize = u8::MAX as usize;
+
+/// Represents the possible errors of the profile format.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ProfileError {
+    /// The buffer is too small for the profile, or the profile is truncated.
+    BufferTooSmall,
+    /// The data does not start with the magic bytes of a profile, e.g. because the storage is
+    /// erased.
+    InvalidMagic,
+    /// The profile has the given version, which is not supported.
+    UnsupportedVersion(u8),
+    /// The profile contains the given number of ADCs, which does not match the expected one.
+    AdcCountMismatch(u8),
+    /// The profile contains an invalid value.
+    InvalidData,
+    /// The CRC of the profile does not match its content.
+    CrcMismatch,
+}
+
+impl From<CodecError> for ProfileError {
+    fn from(x: CodecError) -> Self {
+        match x {
+            CodecError::BufferTooSmall => ProfileError::BufferTooSmall,
+            CodecError::InvalidData => ProfileError::InvalidData,
+        }
+    }
+}
+
+/// Represents the possible errors when storing or loading a profile.
+#[cfg(feature = "embedded-storage")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StorageError<E> {
+    /// The profile cannot be encoded or decoded.
+    Profile(ProfileError),
+    /// The storage returned an error.
+    Storage(E),
+}
+
+#[cfg(feature = "embedded-storage")]
+impl<E> From<ProfileError> for StorageError<E> {
+    fn from(x: ProfileError) -> Self {
+        StorageError::Profile(x)
+    }
+}
+
+/// Contains the scaling of the sensors connected to an ADC, relative to the full-scale values of
+/// the conversion. The scaling accounts for the shunt and the resistor dividers of the board, while
+/// the [`Calibration`] accounts for the tolerances of each unit.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Scaling {
+    /// Multiplier of the current channel.
+    pub current: f32,
+    /// Multiplier of the voltage channel.
+    pub voltage: f32,
+    /// Multiplier of the auxiliary channel, when measuring a voltage.
+    pub aux: f32,
+}
+
+impl Default for Scaling {
+    fn default() -> Self {
+        Self {
+            current: 1.0,
+            voltage: 1.0,
+            aux: 1.0,
+        }
+    }
+}
+
+/// Contains the configuration and calibration profile of `N` ADCs.
+#[derive(Debug, Copy, Clone)]
+pub struct Profile<const N: usize> {
+    /// Chip of each ADC as a [`Chip`].
+    pub chip: [Chip; N],
+    /// Configuration of each ADC as a [`Config`].
+    pub config: [Config; N],
+    /// EMI settings of each ADC as an [`EmiCtrl`].
+    pub emi_ctrl: [EmiCtrl; N],
+    /// Scaling of the sensors of each ADC as a [`Scaling`].
+    pub scaling: [Scaling; N],
+    /// Calibration values of each ADC as a [`Calibration`].
+    pub calibration: [Calibration; N],
+}
+
+impl<const N: usize> Profile<N> {
+    /// Returns the calibration values of each ADC with the scaling of the sensors folded into the
+    /// gains, ready to be given to [`poly::Ade791x::init()`].
+    pub fn calibration(&self) -> [Calibration; N] {
+        core::array::from_fn(|i| {
+            let scaling = &self.scaling[i];
+            let mut calibration = self.calibration[i];
+            calibration.gain.current *= scaling.current;
+            calibration.gain.voltage *= scaling.voltage;
+            calibration.gain.aux = Some(calibration.gain.aux.unwrap_or(1.0) * scaling.aux);
+            calibration
+        })
+    }
+
+    /// Encodes the profile at the beginning of the given buffer, returning the number of bytes
+    /// written.
+    /// # Arguments
+    /// * `buf` - The buffer to write the profile into.
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, ProfileError> {
+        let mut writer = Writer::new(buf);
+        writer.bytes(MAGIC)?;
+        writer.u8(VERSION)?;
+        writer.u8(u8::try_from(N).map_err(|_| ProfileError::AdcCountMismatch(u8::MAX))?)?;
+        writer.u16(0)?;
+        for i in 0..N {
+            let mut record = [0; RECORD_MAX_LEN];
+            let mut record_writer = Writer::new(&mut record);
+            record_writer.chip(self.chip[i])?;
+            record_writer.u8(self.config[i].into())?;
+            record_writer.u8(self.emi_ctrl[i].into())?;
+            record_writer.calibration(&self.calibration[i])?;
+            record_writer.f32(self.scaling[i].current)?;
+            record_writer.f32(self.scaling[i].voltage)?;
+            record_writer.f32(self.scaling[i].aux)?;
+            let len = record_writer.position();
+            writer.u8(len as u8)?;
+            writer.bytes(&record[..len])?;
+        }
+        let len = writer.position();
+        let payload_len = u16::try_from(len - HEADER_LEN).map_err(|_| ProfileError::InvalidData)?;
+        buf[6..HEADER_LEN].copy_from_slice(&payload_len.to_le_bytes());
+        let crc = codec::crc16(buf[..len].iter().copied());
+        buf.get_mut(len..len + CRC_LEN)
+            .ok_or(ProfileError::BufferTooSmall)?
+            .copy_from_slice(&crc.to_le_bytes());
+        Ok(len + CRC_LEN)
+    }
+
+    /// Decodes a profile from the given data, checking its CRC.
+    /// # Arguments
+    /// * `data` - The encoded profile.
+    pub fn decode(data: &[u8]) -> Result<Self, ProfileError> {
+        let len = Self::encoded_len(data)?;
+        let data = data.get(..len).ok_or(ProfileError::BufferTooSmall)?;
+        let (content, crc) = data.split_at(len - CRC_LEN);
+        if codec::crc16(content.iter().copied()) != u16::from_le_bytes([crc[0], crc[1]]) {
+            return Err(ProfileError::CrcMismatch);
+        }
+        let mut reader = Reader::new(&content[HEADER_LEN..]);
+        let mut profile = Self {
+            chip: [Chip::ADE7913; N],
+            config: [Config::default(); N],
+            emi_ctrl: [EmiCtrl::default(); N],
+            scaling: [Scaling::default(); N],
+            calibration: [Calibration::default(); N],
+        };
+        for i in 0..N {
+            let record_len = reader.u8()? as usize;
+            let mut record = Reader::new(reader.bytes(record_len)?);
+            profile.chip[i] = record.chip()?;
+            profile.config[i] = Config::from(record.u8()?);
+            profile.emi_ctrl[i] = EmiCtrl::from(record.u8()?);
+            profile.calibration[i] = record.calibration()?;
+            if !record.is_empty() {
+                profile.scaling[i] = Scaling {
+                    current: record.f32()?,
+                    voltage: record.f32()?,
+                    aux: record.f32()?,
+                };
+            }
+        }
+        if !reader.is_empty() {
+            return Err(ProfileError::InvalidData);
+        }
+        Ok(profile)
+    }
+
+    /// Returns the total length of the profile, given its header.
+    /// # Arguments
+    /// * `header` - The data starting with the header of the profile.
+    fn encoded_len(header: &[u8]) -> Result<usize, ProfileError> {
+        let mut reader = Reader::new(header);
+        if reader.bytes(MAGIC.len())? != MAGIC {
+            return Err(ProfileError::InvalidMagic);
+        }
+        let version = reader.u8()?;
+        if version != VERSION {
+            return Err(ProfileError::UnsupportedVersion(version));
+        }
+        let count = reader.u8()?;
+        if count as usize != N {
+            return Err(ProfileError::AdcCountMismatch(count));
+        }
+        Ok(HEADER_LEN + reader.u16()? as usize + CRC_LEN)
+    }
+
+    /// Stores the profile at the given offset of the storage, erasing the sectors it spans first.
+    /// The buffer is used to encode the profile, and must be large enough to contain it padded to
+    /// the write size of the storage. Returns the number of bytes of the encoded profile.
+    /// # Arguments
+    /// * `flash` - The storage implementing the [`NorFlash`] trait.
+    /// * `offset` - The offset of the profile in the storage, aligned to its erase size.
+    /// * `buf` - The buffer used to encode the profile.
+    #[cfg(feature = "embedded-storage")]
+    pub fn store<F: NorFlash>(
+        &self,
+        flash: &mut F,
+        offset: u32,
+        buf: &mut [u8],
+    ) -> Result<usize, StorageError<F::Error>> {
+        let len = self.encode(buf)?;
+        let write_len = round_up(len, F::WRITE_SIZE);
+        buf.get_mut(len..write_len)
+            .ok_or(ProfileError::BufferTooSmall)?
+            .fill(0xFF);
+        let erase_len = round_up(len, F::ERASE_SIZE) as u32;
+        flash
+            .erase(offset, offset + erase_len)
+            .map_err(StorageError::Storage)?;
+        flash
+            .write(offset, &buf[..write_len])
+            .map_err(StorageError::Storage)?;
+        Ok(len)
+    }
+
+    /// Loads a profile from the given offset of the storage, checking its CRC. The buffer is used
+    /// to read the profile, and must be large enough to contain it padded to the read size of the
+    /// storage.
+    /// # Arguments
+    /// * `flash` - The storage implementing the [`ReadNorFlash`] trait.
+    /// * `offset` - The offset of the profile in the storage.
+    /// * `buf` - The buffer used to read the profile.
+    #[cfg(feature = "embedded-storage")]
+    pub fn load<F: ReadNorFlash>(
+        flash: &mut F,
+        offset: u32,
+        buf: &mut [u8],
+    ) -> Result<Self, StorageError<F::Error>> {
+        let header_len = round_up(HEADER_LEN, F::READ_SIZE);
+        let header = buf
+            .get_mut(..header_len)
+            .ok_or(ProfileError::BufferTooSmall)?;
+        flash.read(offset, header).map_err(StorageError::Storage)?;
+        let len = Self::encoded_len(header)?;
+        let read_len = round_up(len, F::READ_SIZE);
+        let data = buf
+            .get_mut(..read_len)
+            .ok_or(ProfileError::BufferTooSmall)?;
+        flash.read(offset, data).map_err(StorageError::Storage)?;
+        Ok(Self::decode(&data[..len])?)
+    }
+}
+
+/// Returns the given length rounded up to a multiple of the given size.
+#[cfg(feature = "embedded-storage")]
+fn round_up(len: usize, size: usize) -> usize {
+    len.div_ceil(size.max(1)) * size.max(1)
+}
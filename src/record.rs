@@ -0,0 +1,313 @@
+//! Binary format for recording the raw samples of the ADCs and replaying them.
+//!
+//! A recording starts with a header, containing the chip, the configuration, the EMI settings, the
+//! channels read and the calibration values of each ADC, followed by the frames, each containing the raw waveforms of
+//! all the ADCs and, optionally, their ADC CRC, STATUS0 register and counter snapshot. With delta
+//! compression, the waveforms are stored as the difference from the previous frame, encoded as a
+//! zigzag varint, which takes 1 to 5 bytes instead of 3. All the values are little-endian. The
+//! recordings of version 1, without the channels, are replayed as if all the channels were read.
+//!
+//! | Field          | Size          | Content                                                 |
+//! |----------------|---------------|---------------------------------------------------------|
+//! | Magic          | 4             | `ADER`                                                  |
+//! | Version        | 1             | [`VERSION`]                                             |
+//! | ADC count      | 1             | `N`                                                     |
+//! | Flags          | 1             | Bit 0: status stored, bit 1: delta compression          |
+//! | ADCs           | `N` × (4 + …) | Chip, CONFIG, EMI_CTRL, channels and calibration values |
+//! | Frames         | …             | `N` × waveforms (3 × 3 bytes or 3 varints) [+ 5 bytes]  |
+//!
+//! The [`Recorder`] encodes the header and the frames into byte buffers, without allocation. The
+//! [`Replay`] decodes a recording and converts the frames through the same conversion path as
+//! [`poly::Ade791x::get_measurement()`], including the tempco compensation. The interleaved
+//! temperature sampling is not replayed, as it switches the second voltage channel of the ADCs.
+//!
+//! ```rust ignore
+//! // Device
+//! let mut buf = [0; 512];
+//! let header = adc.recording_header(true, true);
+//! let (mut recorder, len) = record::Recorder::new(&header, &mut buf).unwrap();
+//! let len = len + recorder.write_frame(&adc.get_sample().unwrap(), &mut buf[len..]).unwrap();
+//! // Host
+//! let data = std::fs::read("recording.bin").unwrap();
+//! let mut replay = record::Replay::<3>::new(&data).unwrap();
+//! while let Some(measurement) = replay.next_measurement() {
+//!     // Process the measurement
+//! }
+//! ```
+
+use super::codec::{CodecError, Reader, Writer};
+use super::*;
+
+/// Version of the recording format.
+pub const VERSION: u8 = 2;
+
+const MAGIC: &[u8; 4] = b"ADER";
+const FLAG_STATUS: u8 = 0x01;
+const FLAG_DELTA: u8 = 0x02;
+
+/// Represents the possible errors of the recording format.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RecordError {
+    /// The buffer is too small for the data to write, or the recording is truncated.
+    BufferTooSmall,
+    /// The data does not start with the magic bytes of a recording.
+    InvalidMagic,
+    /// The recording has the given version, which is not supported.
+    UnsupportedVersion(u8),
+    /// The recording contains the given number of ADCs, which does not match the expected one.
+    AdcCountMismatch(u8),
+    /// The recording contains an invalid value.
+    InvalidData,
+    /// All the frames of the recording have been read.
+    EndOfRecording,
+}
+
+impl From<CodecError> for RecordError {
+    fn from(x: CodecError) -> Self {
+        match x {
+            CodecError::BufferTooSmall => RecordError::BufferTooSmall,
+            CodecError::InvalidData => RecordError::InvalidData,
+        }
+    }
+}
+
+/// Contains the description of the ADCs of a recording.
+#[derive(Debug, Copy, Clone)]
+pub struct Header<const N: usize> {
+    /// Chip of each ADC as a [`Chip`].
+    pub chip: [Chip; N],
+    /// Configuration of each ADC as a [`Config`].
+    pub config: [Config; N],
+    /// EMI settings of each ADC as an [`EmiCtrl`].
+    pub emi_ctrl: [EmiCtrl; N],
+    /// Channels read from each ADC as a [`Channels`] value. The waveforms of the channels not read
+    /// are recorded as zero.
+    pub channels: [Channels; N],
+    /// Calibration values of each ADC as a [`Calibration`], with the temperature calibration
+    /// resolved.
+    pub calibration: [Calibration; N],
+    /// `true` if the frames contain the ADC CRC, the STATUS0 register and the counter snapshot,
+    /// `false` if they contain the waveforms only.
+    pub status: bool,
+    /// `true` if the waveforms are delta compressed, `false` otherwise.
+    pub delta: bool,
+}
+
+/// Encodes a recording of the samples of `N` ADCs.
+pub struct Recorder<const N: usize> {
+    status: bool,
+    delta: bool,
+    previous: [RawMeasurement; N],
+}
+
+impl<const N: usize> Recorder<N> {
+    /// Creates a new [`Recorder`] and writes the given header at the beginning of the given buffer,
+    /// returning the recorder and the number of bytes written.
+    /// # Arguments
+    /// * `header` - The [`Header`] struct describing the ADCs.
+    /// * `buf` - The buffer to write the header into.
+    pub fn new(header: &Header<N>, buf: &mut [u8]) -> Result<(Self, usize), RecordError> {
+        let mut writer = Writer::new(buf);
+        writer.bytes(MAGIC)?;
+        writer.u8(VERSION)?;
+        writer.u8(u8::try_from(N).map_err(|_| RecordError::AdcCountMismatch(u8::MAX))?)?;
+        let flags =
+            if header.status { FLAG_STATUS } else { 0 } | if header.delta { FLAG_DELTA } else { 0 };
+        writer.u8(flags)?;
+        for i in 0..N {
+            writer.chip(header.chip[i])?;
+            writer.u8(header.config[i].into())?;
+            writer.u8(header.emi_ctrl[i].into())?;
+            writer.channels(header.channels[i])?;
+            writer.calibration(&header.calibration[i])?;
+        }
+        let recorder = Self {
+            status: header.status,
+            delta: header.delta,
+            previous: [RawMeasurement {
+                iwv: 0,
+                v1wv: 0,
+                v2wv: 0,
+            }; N],
+        };
+        Ok((recorder, writer.position()))
+    }
+
+    /// Writes a frame at the beginning of the given buffer, returning the number of bytes written.
+    /// The ADC CRC, the STATUS0 register and the counter snapshot of the samples are written only if
+    /// enabled in the [`Header`]. Samples can be obtained from raw measurements with
+    /// [`Sample::from()`].
+    /// # Arguments
+    /// * `sample` - An array of [`Sample`] structs containing the frame.
+    /// * `buf` - The buffer to write the frame into.
+    pub fn write_frame(
+        &mut self,
+        sample: &[Sample; N],
+        buf: &mut [u8],
+    ) -> Result<usize, RecordError> {
+        let mut writer = Writer::new(buf);
+        for (sample, previous) in sample.iter().zip(&self.previous) {
+            let raw = &sample.raw;
+            if self.delta {
+                writer.varint(raw.iwv.wrapping_sub(previous.iwv))?;
+                writer.varint(raw.v1wv.wrapping_sub(previous.v1wv))?;
+                writer.varint(raw.v2wv.wrapping_sub(previous.v2wv))?;
+            } else {
+                writer.i24(raw.iwv)?;
+                writer.i24(raw.v1wv)?;
+                writer.i24(raw.v2wv)?;
+            }
+            if self.status {
+                writer.u16(sample.adc_crc)?;
+                writer.u8(sample.status0.into())?;
+                writer.u16(sample.cnt_snapshot)?;
+            }
+        }
+        self.previous = sample.map(|s| s.raw);
+        Ok(writer.position())
+    }
+}
+
+/// Replays a recording of the samples of `N` ADCs.
+pub struct Replay<'a, const N: usize> {
+    header: Header<N>,
+    reader: Reader<'a>,
+    previous: [RawMeasurement; N],
+    conversion: [Conversion; N],
+    temperature: [Option<f32>; N],
+    failed: bool,
+}
+
+impl<'a, const N: usize> Replay<'a, N> {
+    /// Creates a new [`Replay`], decoding the header of the given recording.
+    /// # Arguments
+    /// * `data` - The recording.
+    pub fn new(data: &'a [u8]) -> Result<Self, RecordError> {
+        let mut reader = Reader::new(data);
+        if reader.bytes(MAGIC.len())? != MAGIC {
+            return Err(RecordError::InvalidMagic);
+        }
+        let version = reader.u8()?;
+        if version == 0 || version > VERSION {
+            return Err(RecordError::UnsupportedVersion(version));
+        }
+        let count = reader.u8()?;
+        if count as usize != N {
+            return Err(RecordError::AdcCountMismatch(count));
+        }
+        let flags = reader.u8()?;
+        let mut header = Header {
+            chip: [Chip::ADE7912; N],
+            config: [Config::default(); N],
+            emi_ctrl: [EmiCtrl::default(); N],
+            channels: [Channels::All; N],
+            calibration: [Calibration::default(); N],
+            status: flags & FLAG_STATUS != 0,
+            delta: flags & FLAG_DELTA != 0,
+        };
+        for i in 0..N {
+            header.chip[i] = reader.chip()?;
+            header.config[i] = reader.u8()?.into();
+            header.emi_ctrl[i] = reader.u8()?.into();
+            if version > 1 {
+                header.channels[i] = reader.channels()?;
+            }
+            header.calibration[i] = reader.calibration()?;
+        }
+        let conversion = core::array::from_fn(|i| {
+            Conversion::new(header.chip[i], &header.config[i], &header.calibration[i])
+        });
+        Ok(Self {
+            header,
+            reader,
+            previous: [RawMeasurement {
+                iwv: 0,
+                v1wv: 0,
+                v2wv: 0,
+            }; N],
+            conversion,
+            temperature: [None; N],
+            failed: false,
+        })
+    }
+
+    /// Returns the [`Header`] struct of the recording.
+    pub fn header(&self) -> &Header<N> {
+        &self.header
+    }
+
+    /// Returns the [`Conversion`] structs used by [`Self::next_measurement()`].
+    pub fn conversion(&self) -> [Conversion; N] {
+        self.conversion
+    }
+
+    /// Returns the next frame of the recording as an array of [`Sample`] structs, or [`None`] at
+    /// the end of the recording. The ADC CRC, the STATUS0 register and the counter snapshot are set
+    /// to zero if they were not recorded. After a decoding error, the position of the next frame is
+    /// unknown, so the replay ends and [`None`] is returned from then on.
+    pub fn next_sample(&mut self) -> Option<Result<[Sample; N], RecordError>> {
+        if self.failed || self.reader.is_empty() {
+            return None;
+        }
+        let sample = self.read_frame();
+        self.failed = sample.is_err();
+        Some(sample)
+    }
+
+    /// Returns the next frame of the recording as an array of [`RawMeasurement`] structs, or
+    /// [`None`] at the end of the recording.
+    pub fn next_raw_measurement(&mut self) -> Option<Result<[RawMeasurement; N], RecordError>> {
+        self.next_sample().map(|s| s.map(|s| s.map(|s| s.raw)))
+    }
+
+    /// Returns the next frame of the recording converted to an array of [`Measurement`] structs,
+    /// or [`None`] at the end of the recording.
+    pub fn next_measurement(&mut self) -> Option<Result<[Measurement; N], RecordError>> {
+        let raw_measurement = match self.next_raw_measurement()? {
+            Ok(raw_measurement) => raw_measurement,
+            Err(e) => return Some(Err(e)),
+        };
+        Some(Ok(core::array::from_fn(|i| {
+            let mut measurement = self.conversion[i].convert(&raw_measurement[i]);
+            self.header.channels[i].mask(&mut measurement);
+            if let MeasurementAux::Temperature(temperature) = measurement.aux {
+                self.temperature[i] = Some(temperature);
+            }
+            if let Some(temperature) = self.temperature[i] {
+                self.header.calibration[i]
+                    .tempco
+                    .compensate(temperature, &mut measurement);
+            }
+            measurement
+        })))
+    }
+
+    /// Decodes a frame.
+    fn read_frame(&mut self) -> Result<[Sample; N], RecordError> {
+        let mut sample = [Sample::from(RawMeasurement {
+            iwv: 0,
+            v1wv: 0,
+            v2wv: 0,
+        }); N];
+        for (sample, previous) in sample.iter_mut().zip(&self.previous) {
+            let raw = &mut sample.raw;
+            if self.header.delta {
+                raw.iwv = previous.iwv.wrapping_add(self.reader.varint()?);
+                raw.v1wv = previous.v1wv.wrapping_add(self.reader.varint()?);
+                raw.v2wv = previous.v2wv.wrapping_add(self.reader.varint()?);
+            } else {
+                raw.iwv = self.reader.i24()?;
+                raw.v1wv = self.reader.i24()?;
+                raw.v2wv = self.reader.i24()?;
+            }
+            if self.header.status {
+                sample.adc_crc = self.reader.u16()?;
+                sample.status0 = self.reader.u8()?.into();
+                sample.cnt_snapshot = self.reader.u16()?;
+            }
+        }
+        self.previous = sample.map(|s| s.raw);
+        Ok(sample)
+    }
+}
@@ -1,5 +1,10 @@
+use super::{Chip, Error};
+
 /// Configuration struct.
-#[derive(Default, Debug, Copy, Clone)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Config {
     /// Enables CLKOUT functionality at the CLKOUT/DREADY pin. When `clkout_en = false`, the default
     /// value, DREADY functionality is enabled. When `clkout_en = true`, CLKOUT functionality is
@@ -18,7 +23,8 @@ pub struct Config {
     /// This field selects the ADC output frequency.
     pub adc_freq: AdcFreqVal,
     /// When this field is set to `true`,a software reset is initiated. This field clears itself to
-    /// `false` after one CLKIN cycle.
+    /// `false` after one CLKIN cycle. This field is never serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub swrst: bool,
     /// Selects the bandwidth of the digital low-pass filter of the ADC. When `bw = false`, the
     /// default value, the bandwidth is 3.3 kHz. When `bw = true`, the bandwidth is 2 kHz. The
@@ -28,12 +34,18 @@ pub struct Config {
 
 /// Represents the possible ADC frequency values.
 #[repr(u8)]
-#[derive(Default, Debug, Copy, Clone)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AdcFreqVal {
     #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "8kHz"))]
     KHz8 = 0x00,
+    #[cfg_attr(feature = "serde", serde(rename = "4kHz"))]
     KHz4 = 0x01,
+    #[cfg_attr(feature = "serde", serde(rename = "2kHz"))]
     KHz2 = 0x02,
+    #[cfg_attr(feature = "serde", serde(rename = "1kHz"))]
     KHz1 = 0x03,
 }
 
@@ -61,6 +73,18 @@ impl From<Config> for u8 {
     }
 }
 
+impl AdcFreqVal {
+    /// Returns the ADC output frequency in Hz.
+    pub fn hz(&self) -> u32 {
+        match self {
+            AdcFreqVal::KHz8 => 8000,
+            AdcFreqVal::KHz4 => 4000,
+            AdcFreqVal::KHz2 => 2000,
+            AdcFreqVal::KHz1 => 1000,
+        }
+    }
+}
+
 impl From<u8> for AdcFreqVal {
     fn from(x: u8) -> Self {
         match x & 0x03 {
@@ -73,7 +97,10 @@ impl From<u8> for AdcFreqVal {
 }
 
 /// Status struct.
-#[derive(Default, Debug, Copy, Clone)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "u8", into = "u8"))]
 pub struct Status0 {
     /// During reset, the `reset_on` field is set to `true`. When the reset ends and the
     /// ADE7912/ADE7913 are ready to be configured, the `reset_on` field is cleared to `false`.
@@ -95,9 +122,47 @@ impl From<u8> for Status0 {
     }
 }
 
+impl From<Status0> for u8 {
+    fn from(x: Status0) -> Self {
+        (x.ic_prot as u8) << 2 | (x.crc_stat as u8) << 1 | (x.reset_on as u8)
+    }
+}
+
+/// STATUS1 register struct. Reports the silicon version and whether the ADC outputs were read
+/// while not available.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "u8", into = "u8"))]
+pub struct Status1 {
+    /// Version of the ADE7912/ADE7913 silicon.
+    pub version: u8,
+    /// When the `adc_na` field is `true`, the ADC outputs were read while not available, i.e.
+    /// during the update of the waveform registers.
+    pub adc_na: bool,
+}
+
+impl From<u8> for Status1 {
+    fn from(x: u8) -> Self {
+        Status1 {
+            version: x & 0x07,
+            adc_na: (x & 0x08) != 0,
+        }
+    }
+}
+
+impl From<Status1> for u8 {
+    fn from(x: Status1) -> Self {
+        (x.adc_na as u8) << 3 | (x.version & 0x07)
+    }
+}
+
 /// EMI control struct. Manages the PWM control block of the isolated dc-to-dc converter to reduce
 /// EMI emissions.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "u8", into = "u8"))]
 pub struct EmiCtrl {
     /// Controls the PWM control block pulse during Slot 0 of the CLKIN/4 clock.
     pub slot0: bool,
@@ -123,6 +188,85 @@ impl Default for EmiCtrl {
     }
 }
 
+impl EmiCtrl {
+    /// Returns the recommended [`EmiCtrl`] assignment for a polyphase metering system composed of
+    /// the given chips. The 8 slots of the CLKIN/4 clock are split between the ADCs without
+    /// overlapping, and the slots of each ADC are spread evenly in time. When the slots cannot be
+    /// split evenly, the remaining ones are given to the ADE7913 chips first, as they draw more
+    /// power from the isolated dc-to-dc converter. With more than 8 ADCs, each ADC gets a single
+    /// slot and the slots are shared.
+    /// # Arguments
+    /// * `chips` - The array of chips as [`Chip`].
+    pub fn allocate<const N: usize>(chips: [Chip; N]) -> [EmiCtrl; N] {
+        let mut masks = [0_u8; N];
+        if N == 0 {
+            return masks.map(EmiCtrl::from);
+        }
+        if N > 8 {
+            for (i, mask) in masks.iter_mut().enumerate() {
+                *mask = 0x01 << (i % 8);
+            }
+            return masks.map(EmiCtrl::from);
+        }
+        let mut count = [8 / N; N];
+        let mut remainder = 8 % N;
+        for chip in [Chip::ADE7913, Chip::ADE7912] {
+            for i in 0..N {
+                if remainder > 0 && chips[i] == chip {
+                    count[i] += 1;
+                    remainder -= 1;
+                }
+            }
+        }
+        let mut i = 0;
+        for slot in 0..8 {
+            while count[i] == 0 {
+                i = (i + 1) % N;
+            }
+            masks[i] |= 0x01 << slot;
+            count[i] -= 1;
+            i = (i + 1) % N;
+        }
+        masks.map(EmiCtrl::from)
+    }
+
+    /// Checks that every ADC of the given [`EmiCtrl`] assignment has at least one slot enabled.
+    /// # Arguments
+    /// * `emi_ctrl` - The array of [`EmiCtrl`] structs to check.
+    pub(crate) fn validate<S, P, const N: usize>(
+        emi_ctrl: &[EmiCtrl; N],
+    ) -> Result<(), Error<S, P>> {
+        for (device, emi_ctrl) in emi_ctrl.iter().enumerate() {
+            if u8::from(*emi_ctrl) == 0 {
+                return Err(Error::EmiCtrlEmpty { device });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the given [`EmiCtrl`] assignment is valid as in [`Self::validate()`] and, with
+    /// up to 8 ADCs, that no slot is shared between ADCs.
+    /// # Arguments
+    /// * `emi_ctrl` - The array of [`EmiCtrl`] structs to check.
+    pub(crate) fn validate_strict<S, P, const N: usize>(
+        emi_ctrl: &[EmiCtrl; N],
+    ) -> Result<(), Error<S, P>> {
+        Self::validate(emi_ctrl)?;
+        if N > 8 {
+            return Ok(());
+        }
+        let mut used = 0_u8;
+        for (device, emi_ctrl) in emi_ctrl.iter().enumerate() {
+            let mask = u8::from(*emi_ctrl);
+            if used & mask != 0 {
+                return Err(Error::EmiCtrlOverlap { device });
+            }
+            used |= mask;
+        }
+        Ok(())
+    }
+}
+
 impl From<u8> for EmiCtrl {
     fn from(x: u8) -> Self {
         Self {
@@ -199,25 +343,39 @@ impl From<[u8; 15]> for BurstRead {
     }
 }
 
-/// Represents the registries of the ADC.
-#[repr(u8)]
-#[allow(dead_code)]
-#[derive(PartialEq, Copy, Clone)]
-pub(crate) enum Register {
+/// Represents the registers of the ADC.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Register {
+    /// Instantaneous value of the current channel.
     Iwv,
+    /// Instantaneous value of the voltage channel.
     V1wv,
+    /// Instantaneous value of the second voltage channel.
     V2wv,
+    /// CRC of the waveform registers.
     AdcCrc,
+    /// CRC of the configuration registers.
     CtrlCrc,
+    /// Snapshot of the internal counter.
     CntSnapshot,
+    /// Configuration register.
     Config,
+    /// Status register.
     Status0,
+    /// Protection of the configuration registers.
     Lock,
+    /// Synchronization and snapshot commands.
     SyncSnap,
+    /// Least significant byte of the internal counter.
     Counter0,
+    /// Most significant byte of the internal counter.
     Counter1,
+    /// EMI control register.
     EmiCtrl,
+    /// Second status register.
     Status1,
+    /// Offset correction of the temperature sensor.
     Tempos,
 }
 
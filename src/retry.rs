@@ -0,0 +1,88 @@
+use super::*;
+
+/// Selects the errors retried by a [`RetryPolicy`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RetryOn {
+    /// Retry on [`Error::SpiError`] and [`Error::PinError`].
+    pub spi: bool,
+    /// Retry on [`Error::RegisterContentMismatch`], writing the register again. A mismatch left
+    /// after the retries is reported as [`Recovery::Reinit`], see [`Error::recovery()`].
+    pub mismatch: bool,
+    /// Retry on [`Error::CrcMismatch`], reading the waveforms again.
+    pub crc: bool,
+}
+
+impl Default for RetryOn {
+    fn default() -> Self {
+        Self {
+            spi: true,
+            mismatch: true,
+            crc: true,
+        }
+    }
+}
+
+/// Describes how the driver retries the SPI transactions failed because of glitches on the bus.
+/// Every register transaction except the sync and snap commands is retried as a whole, i.e. the
+/// checked register writings, the burst reads of the measurements, the lock and power-down
+/// writings, the synchronization adjustment and the register dumps, releasing the CS pin before
+/// each new attempt. The default policy performs a single attempt, i.e. no retries.
+///
+/// The backoff is applied only during the initialization, including the wait for the reset, as
+/// it is the only step that takes a delay source: every other transaction is retried immediately,
+/// as the measurement path cannot block. A bus that needs time to recover after a glitch is better
+/// served by retrying the whole operation from the application.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RetryPolicy {
+    /// Maximum number of attempts of each transaction, including the first one. Values lower than
+    /// 1 are treated as 1.
+    pub max_attempts: u8,
+    /// Delay in ms between the attempts of the initialization. The other transactions are retried
+    /// immediately, as the waveform registers keep their content until the next DREADY pulse.
+    pub backoff_ms: u32,
+    /// The errors that are retried as a [`RetryOn`] struct.
+    pub retry_on: RetryOn,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            backoff_ms: 1,
+            retry_on: RetryOn::default(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns `true` if the given error is retried by the policy, `false` otherwise.
+    /// # Arguments
+    /// * `error` - The [`Error`] to check.
+    pub fn is_retryable<S, P>(&self, error: &Error<S, P>) -> bool {
+        match error {
+            Error::SpiError(_) | Error::PinError(_) => self.retry_on.spi,
+            Error::RegisterContentMismatch { .. } => self.retry_on.mismatch,
+            Error::CrcMismatch { .. } => self.retry_on.crc,
+            _ => false,
+        }
+    }
+}
+
+/// Contains the retry counters of an ADC, for diagnostics. The counters wrap around on overflow.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RetryCounters {
+    /// Number of attempts performed after a failed one.
+    pub retries: u32,
+    /// Number of transactions that succeeded after at least one retry.
+    pub recovered: u32,
+    /// Number of transactions that failed after all the attempts allowed by the policy.
+    pub exhausted: u32,
+}
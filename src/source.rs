@@ -0,0 +1,124 @@
+use super::*;
+
+/// Represents a source of samples from `N` ADCs, either the drivers or a recording, so that
+/// metering and analytics code can be written independently of the hardware.
+pub trait MeasurementSource<const N: usize> {
+    /// The error returned by the source.
+    type Error;
+
+    /// Returns the next measurement from the ADCs as an array of [`RawMeasurement`] structs.
+    fn get_raw_measurement(&mut self) -> Result<[RawMeasurement; N], Self::Error>;
+
+    /// Returns the next measurement from the ADCs as an array of [`Measurement`] structs.
+    fn get_measurement(&mut self) -> Result<[Measurement; N], Self::Error>;
+
+    /// Returns the ADC output frequency in Hz.
+    fn sample_rate(&self) -> u32;
+
+    /// Returns the chip of each ADC as a [`Chip`].
+    fn chips(&self) -> [Chip; N];
+
+    /// Returns the channels read from each ADC as a [`Channels`] value.
+    fn channels(&self) -> [Channels; N];
+
+    /// Returns the [`Conversion`] structs used to convert the raw measurements of each ADC.
+    fn conversion(&self) -> [Conversion; N];
+}
+
+impl<SPI, CS, DR, S, P> MeasurementSource<1> for Ade791x<SPI, CS, DR>
+where
+    SPI: spi::Transfer<u8, Error = S>,
+    CS: OutputPin<Error = P>,
+{
+    type Error = Error<S, P>;
+
+    fn get_raw_measurement(&mut self) -> Result<[RawMeasurement; 1], Self::Error> {
+        Ade791x::get_raw_measurement(self).map(|m| [m])
+    }
+
+    fn get_measurement(&mut self) -> Result<[Measurement; 1], Self::Error> {
+        Ade791x::get_measurement(self).map(|m| [m])
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.adc.sample_rate()
+    }
+
+    fn chips(&self) -> [Chip; 1] {
+        self.adc.chips()
+    }
+
+    fn channels(&self) -> [Channels; 1] {
+        self.adc.channels()
+    }
+
+    fn conversion(&self) -> [Conversion; 1] {
+        [Ade791x::conversion(self)]
+    }
+}
+
+impl<SPI, CS, DR, S, P, const N: usize> MeasurementSource<N> for poly::Ade791x<SPI, CS, N, DR>
+where
+    SPI: spi::Transfer<u8, Error = S>,
+    CS: OutputPin<Error = P>,
+{
+    type Error = Error<S, P>;
+
+    fn get_raw_measurement(&mut self) -> Result<[RawMeasurement; N], Self::Error> {
+        poly::Ade791x::get_raw_measurement(self)
+    }
+
+    fn get_measurement(&mut self) -> Result<[Measurement; N], Self::Error> {
+        poly::Ade791x::get_measurement(self)
+    }
+
+    fn sample_rate(&self) -> u32 {
+        poly::Ade791x::sample_rate(self)
+    }
+
+    fn chips(&self) -> [Chip; N] {
+        poly::Ade791x::chips(self)
+    }
+
+    fn channels(&self) -> [Channels; N] {
+        poly::Ade791x::channels(self)
+    }
+
+    fn conversion(&self) -> [Conversion; N] {
+        poly::Ade791x::conversion(self)
+    }
+}
+
+impl<const N: usize> MeasurementSource<N> for record::Replay<'_, N> {
+    type Error = record::RecordError;
+
+    fn get_raw_measurement(&mut self) -> Result<[RawMeasurement; N], Self::Error> {
+        self.next_raw_measurement()
+            .unwrap_or(Err(record::RecordError::EndOfRecording))
+    }
+
+    fn get_measurement(&mut self) -> Result<[Measurement; N], Self::Error> {
+        self.next_measurement()
+            .unwrap_or(Err(record::RecordError::EndOfRecording))
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.header()
+            .config
+            .first()
+            .map_or(AdcFreqVal::default(), |c| c.adc_freq)
+            .hz()
+    }
+
+    fn chips(&self) -> [Chip; N] {
+        self.header().chip
+    }
+
+    fn channels(&self) -> [Channels; N] {
+        self.header().channels
+    }
+
+    fn conversion(&self) -> [Conversion; N] {
+        record::Replay::conversion(self)
+    }
+}
@@ -0,0 +1,336 @@
+//! Split of a polyphase metering system into an ISR side and a task side.
+//!
+//! [`poly::Ade791x::split()`] consumes the driver and returns a [`SampleReader`], meant to be
+//! called from the DREADY ISR, and a [`Controller`], meant to be used from a lower-priority task.
+//! The two sides only communicate through a [`Shared`] struct, without any lock: the samples are
+//! pushed by the reader into a single-producer/single-consumer ring buffer, and the operations that
+//! need the SPI bus, like the synchronization adjustment, are posted by the controller into a
+//! single-slot mailbox and executed by the reader after the next sample is read. The ring buffer and
+//! the mailbox only rely on atomic loads and stores, so they are also available on targets without
+//! compare-and-swap instructions.
+//!
+//! ```rust ignore
+//! static SHARED: split::Shared<3, 32> = split::Shared::new();
+//! let (mut reader, mut controller) = adc.split(&SHARED);
+//! // DREADY ISR
+//! reader.read().ok();
+//! // Task
+//! while let Some(measurement) = controller.pop_measurement() {
+//!     // Process the measurement
+//! }
+//! ```
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU8, AtomicUsize, Ordering};
+
+use super::*;
+
+const MAILBOX_IDLE: u8 = 0;
+const MAILBOX_PENDING: u8 = 1;
+const MAILBOX_DONE: u8 = 2;
+
+/// Represents the operations that the [`Controller`] can request to the [`SampleReader`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Command<const N: usize> {
+    /// Adjusts the synchronization of the ADCs. See [`poly::Ade791x::adjust_sync()`].
+    AdjustSync,
+    /// Powers-down the ADCs. See [`poly::Ade791x::powerdown()`].
+    Powerdown,
+    /// Wakes-up the ADCs. See [`poly::Ade791x::wakeup()`].
+    Wakeup,
+    /// Powers-down the ADC at the given index. See [`poly::Ade791x::powerdown_device()`].
+    PowerdownDevice(usize),
+    /// Wakes-up the ADC at the given index and synchronizes it. See
+    /// [`poly::Ade791x::wakeup_device()`].
+    WakeupDevice(usize),
+    /// Selects the channels read for each ADC. See [`poly::Ade791x::set_channels()`].
+    SetChannels([Channels; N]),
+}
+
+/// Represents the outcome of a [`Command`] executed by the [`SampleReader`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Response<const N: usize> {
+    /// The command was executed successfully.
+    Done,
+    /// The synchronization was adjusted, with the given drift values in counts.
+    Drift([i16; N]),
+    /// The command failed. The error is returned by [`SampleReader::read()`].
+    Failed,
+}
+
+/// Contains the state shared between the [`SampleReader`] and the [`Controller`] of a polyphase
+/// metering system composed of `N` ADCs, with a ring buffer of `L` samples. A [`Shared`] struct can
+/// be used by a single [`poly::Ade791x::split()`], so it is usually placed in a `static`.
+pub struct Shared<const N: usize, const L: usize> {
+    claimed: AtomicBool,
+    buffer: UnsafeCell<[[RawMeasurement; N]; L]>,
+    channels: UnsafeCell<[[Channels; N]; L]>,
+    head: AtomicUsize,
+    tail: AtomicUsize,
+    overruns: AtomicU32,
+    errors: AtomicU32,
+    mailbox: AtomicU8,
+    command: UnsafeCell<Command<N>>,
+    response: UnsafeCell<Response<N>>,
+}
+
+// SAFETY: the cells are accessed by at most one `SampleReader` and one `Controller`, created
+// together by `poly::Ade791x::split()` after claiming the struct. The `head` and `tail` indices are
+// kept modulo `2 * L`, so they always map to distinct slots unless the ring buffer is empty or full.
+// The ring buffer slots between `head` and `tail`, along with their channels, are only read by the
+// controller and the others are only written by the reader.
+// The command cell is only written by the controller while the mailbox is idle and only read by the
+// reader while it is pending, and vice versa for the response cell while it is done.
+unsafe impl<const N: usize, const L: usize> Sync for Shared<N, L> {}
+
+impl<const N: usize, const L: usize> Default for Shared<N, L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, const L: usize> Shared<N, L> {
+    /// Creates a new [`Shared`] struct with an empty ring buffer and an idle mailbox.
+    pub const fn new() -> Self {
+        Self {
+            claimed: AtomicBool::new(false),
+            buffer: UnsafeCell::new(
+                [[RawMeasurement {
+                    iwv: 0,
+                    v1wv: 0,
+                    v2wv: 0,
+                }; N]; L],
+            ),
+            channels: UnsafeCell::new([[Channels::All; N]; L]),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            overruns: AtomicU32::new(0),
+            errors: AtomicU32::new(0),
+            mailbox: AtomicU8::new(MAILBOX_IDLE),
+            command: UnsafeCell::new(Command::AdjustSync),
+            response: UnsafeCell::new(Response::Done),
+        }
+    }
+
+    /// Marks the struct as used by a split, returning `false` if it was already used. Targets
+    /// without compare-and-swap instructions fall back to a load and a store, as the split is
+    /// performed once at start-up from a single context.
+    pub(crate) fn claim(&self) -> bool {
+        #[cfg(target_has_atomic = "8")]
+        {
+            !self.claimed.swap(true, Ordering::AcqRel)
+        }
+        #[cfg(not(target_has_atomic = "8"))]
+        {
+            if self.claimed.load(Ordering::Acquire) {
+                return false;
+            }
+            self.claimed.store(true, Ordering::Release);
+            true
+        }
+    }
+}
+
+/// Returns the number of samples between the given ring buffer indices, kept modulo `2 * L`.
+/// # Arguments
+/// * `head` - The index of the oldest sample.
+/// * `tail` - The index of the next sample to write.
+fn distance<const L: usize>(head: usize, tail: usize) -> usize {
+    (tail + 2 * L - head) % (2 * L)
+}
+
+/// Returns the ring buffer index following the given one, kept modulo `2 * L`.
+/// # Arguments
+/// * `index` - The ring buffer index.
+fn next<const L: usize>(index: usize) -> usize {
+    (index + 1) % (2 * L)
+}
+
+/// Represents the ISR side of a polyphase metering system, reading the samples from the ADCs and
+/// executing the commands posted by the [`Controller`].
+pub struct SampleReader<'a, SPI, CS, const N: usize, const L: usize, DR = NoDready> {
+    adc: poly::Ade791x<SPI, CS, N, DR>,
+    shared: &'a Shared<N, L>,
+}
+
+impl<'a, SPI, CS, DR, S, P, const N: usize, const L: usize> SampleReader<'a, SPI, CS, N, L, DR>
+where
+    SPI: spi::Transfer<u8, Error = S>,
+    CS: OutputPin<Error = P>,
+{
+    pub(crate) fn new(adc: poly::Ade791x<SPI, CS, N, DR>, shared: &'a Shared<N, L>) -> Self {
+        Self { adc, shared }
+    }
+
+    /// Reads the latest available measurement from the ADCs and pushes it into the ring buffer.
+    /// Call this method inside the ISR from the DREADY pin. If the ring buffer is full, the
+    /// measurement is dropped and the overrun counter is incremented. After the measurement is read,
+    /// the command posted by the [`Controller`], if any, is executed.
+    pub fn read(&mut self) -> Result<(), Error<S, P>> {
+        let raw_measurement = self.adc.get_raw_measurement().inspect_err(|_| {
+            increment(&self.shared.errors);
+        })?;
+        let tail = self.shared.tail.load(Ordering::Relaxed);
+        let head = self.shared.head.load(Ordering::Acquire);
+        if distance::<L>(head, tail) >= L {
+            increment(&self.shared.overruns);
+        } else {
+            // SAFETY: the slot at `tail` is not visible to the controller until `tail` is updated.
+            unsafe {
+                (*self.shared.buffer.get())[tail % L] = raw_measurement;
+                (*self.shared.channels.get())[tail % L] = self.adc.channels();
+            }
+            self.shared.tail.store(next::<L>(tail), Ordering::Release);
+        }
+        self.service()
+    }
+
+    /// Executes the command posted by the [`Controller`], if any.
+    fn service(&mut self) -> Result<(), Error<S, P>> {
+        if self.shared.mailbox.load(Ordering::Acquire) != MAILBOX_PENDING {
+            return Ok(());
+        }
+        // SAFETY: the command cell is not written by the controller while the mailbox is pending.
+        let command = unsafe { *self.shared.command.get() };
+        let result = match command {
+            Command::AdjustSync => self.adc.adjust_sync().map(Response::Drift),
+            Command::Powerdown => self.adc.powerdown().map(|_| Response::Done),
+            Command::Wakeup => self.adc.wakeup().map(|_| Response::Done),
+            Command::PowerdownDevice(index) => {
+                self.adc.powerdown_device(index).map(|_| Response::Done)
+            }
+            Command::WakeupDevice(index) => self.adc.wakeup_device(index).map(|drift| {
+                let mut drifts = [0; N];
+                drifts[index] = drift;
+                Response::Drift(drifts)
+            }),
+            Command::SetChannels(channels) => {
+                self.adc.set_channels(channels).map(|_| Response::Done)
+            }
+        };
+        // SAFETY: the response cell is not read by the controller until the mailbox is done.
+        unsafe { *self.shared.response.get() = *result.as_ref().unwrap_or(&Response::Failed) };
+        self.shared.mailbox.store(MAILBOX_DONE, Ordering::Release);
+        result.map(|_| ()).inspect_err(|_| {
+            increment(&self.shared.errors);
+        })
+    }
+
+    /// Returns the driver, releasing the [`Shared`] struct.
+    pub fn into_inner(self) -> poly::Ade791x<SPI, CS, N, DR> {
+        self.adc
+    }
+}
+
+/// Represents the task side of a polyphase metering system, consuming the samples read by the
+/// [`SampleReader`], posting commands to it and monitoring the health of the acquisition.
+pub struct Controller<'a, const N: usize, const L: usize> {
+    shared: &'a Shared<N, L>,
+    conversion: [Conversion; N],
+}
+
+impl<'a, const N: usize, const L: usize> Controller<'a, N, L> {
+    pub(crate) fn new(shared: &'a Shared<N, L>, conversion: [Conversion; N]) -> Self {
+        Self { shared, conversion }
+    }
+
+    /// Pops the oldest measurement from the ring buffer as an array of [`RawMeasurement`] structs.
+    pub fn pop(&mut self) -> Option<[RawMeasurement; N]> {
+        self.pop_slot().map(|(raw_measurement, _)| raw_measurement)
+    }
+
+    /// Pops the oldest measurement from the ring buffer, along with the channels it was read with.
+    fn pop_slot(&mut self) -> Option<([RawMeasurement; N], [Channels; N])> {
+        let head = self.shared.head.load(Ordering::Relaxed);
+        let tail = self.shared.tail.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+        // SAFETY: the slot at `head` is not written by the reader until `head` is updated.
+        let slot = unsafe {
+            (
+                (*self.shared.buffer.get())[head % L],
+                (*self.shared.channels.get())[head % L],
+            )
+        };
+        self.shared.head.store(next::<L>(head), Ordering::Release);
+        Some(slot)
+    }
+
+    /// Pops the oldest measurement from the ring buffer, converting it to an array of
+    /// [`Measurement`] structs using the [`Conversion`] structs of the driver at the time of the
+    /// split. The fields of the channels that were not read are flagged as unavailable, see
+    /// [`poly::Ade791x::set_channels()`]. The tempco compensation and the interleaved temperature
+    /// sampling are not applied.
+    pub fn pop_measurement(&mut self) -> Option<[Measurement; N]> {
+        self.pop_slot().map(|(raw, channels)| {
+            core::array::from_fn(|i| {
+                let mut measurement = self.conversion[i].convert(&raw[i]);
+                channels[i].mask(&mut measurement);
+                measurement
+            })
+        })
+    }
+
+    /// Returns the number of measurements in the ring buffer.
+    pub fn len(&self) -> usize {
+        let head = self.shared.head.load(Ordering::Relaxed);
+        distance::<L>(head, self.shared.tail.load(Ordering::Acquire))
+    }
+
+    /// Returns `true` if the ring buffer is empty, `false` otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of measurements dropped because the ring buffer was full.
+    pub fn overruns(&self) -> u32 {
+        self.shared.overruns.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of failed reads and commands.
+    pub fn errors(&self) -> u32 {
+        self.shared.errors.load(Ordering::Relaxed)
+    }
+
+    /// Posts a command to the [`SampleReader`], to be executed after the next sample is read. Only
+    /// one command can be pending at a time: if the mailbox is not idle, the command is returned
+    /// back. The outcome of the command must be taken with [`Self::response()`] before posting a
+    /// new one.
+    /// # Arguments
+    /// * `command` - The [`Command`] to post.
+    pub fn post(&mut self, command: Command<N>) -> Result<(), Command<N>> {
+        if self.shared.mailbox.load(Ordering::Acquire) != MAILBOX_IDLE {
+            return Err(command);
+        }
+        // SAFETY: the command cell is not read by the reader while the mailbox is idle.
+        unsafe { *self.shared.command.get() = command };
+        self.shared
+            .mailbox
+            .store(MAILBOX_PENDING, Ordering::Release);
+        Ok(())
+    }
+
+    /// Takes the outcome of the posted command, if it has been executed, leaving the mailbox idle.
+    pub fn response(&mut self) -> Option<Response<N>> {
+        if self.shared.mailbox.load(Ordering::Acquire) != MAILBOX_DONE {
+            return None;
+        }
+        // SAFETY: the response cell is not written by the reader while the mailbox is done.
+        let response = unsafe { *self.shared.response.get() };
+        self.shared.mailbox.store(MAILBOX_IDLE, Ordering::Release);
+        Some(response)
+    }
+}
+
+/// Increments a counter with a load and a store, as it is only written by the reader.
+/// # Arguments
+/// * `counter` - The counter to increment.
+fn increment(counter: &AtomicU32) {
+    counter.store(
+        counter.load(Ordering::Relaxed).wrapping_add(1),
+        Ordering::Relaxed,
+    );
+}
@@ -0,0 +1,186 @@
+//! Supervision of the synchronization of polyphase metering systems.
+//!
+//! The [`SyncSupervisor`] keeps a history of the drift values returned by
+//! [`poly::Ade791x::adjust_sync()`](crate::poly::Ade791x::adjust_sync), computes their statistics,
+//! adapts the interval between two adjustments to the observed drift rate, and raises an alarm
+//! when an ADC repeatedly drifts by more than ±1 counts, which points to a broken CLKOUT chain.
+//!
+//! ```rust ignore
+//! let mut supervisor = sync::SyncSupervisor::<3, 16>::default();
+//! loop {
+//!     let drift = adc.adjust_sync().unwrap();
+//!     if supervisor.record(drift).contains(&true) {
+//!         // Check the CLKOUT chain
+//!     }
+//!     delay.delay_ms(supervisor.interval_ms());
+//! }
+//! ```
+
+/// Contains the configuration of a [`SyncSupervisor`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SupervisorConfig {
+    /// Initial interval between two synchronization adjustments in milliseconds.
+    pub initial_interval_ms: u32,
+    /// Minimum interval between two synchronization adjustments in milliseconds.
+    pub min_interval_ms: u32,
+    /// Maximum interval between two synchronization adjustments in milliseconds.
+    pub max_interval_ms: u32,
+    /// Number of consecutive adjustments with a drift exceeding ±1 counts that raise the alarm for
+    /// an ADC.
+    pub alarm_threshold: u16,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            initial_interval_ms: 2000,
+            min_interval_ms: 250,
+            max_interval_ms: 16000,
+            alarm_threshold: 3,
+        }
+    }
+}
+
+/// Contains the statistics of the drift of an ADC over the recorded history.
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DriftStats {
+    /// Number of drift values in the history.
+    pub count: usize,
+    /// Minimum drift value in counts.
+    pub min: i16,
+    /// Maximum drift value in counts.
+    pub max: i16,
+    /// Mean drift value in counts.
+    pub mean: f32,
+    /// Mean absolute drift value in counts.
+    pub mean_abs: f32,
+    /// Number of drift values exceeding ±1 counts.
+    pub excursions: usize,
+}
+
+/// Supervises the synchronization of a polyphase metering system composed of `N` ADCs, keeping a
+/// history of the last `H` drift values of each ADC.
+pub struct SyncSupervisor<const N: usize, const H: usize> {
+    config: SupervisorConfig,
+    history: [[i16; N]; H],
+    len: usize,
+    next: usize,
+    interval_ms: u32,
+    consecutive: [u16; N],
+    alarms: [bool; N],
+}
+
+impl<const N: usize, const H: usize> Default for SyncSupervisor<N, H> {
+    fn default() -> Self {
+        Self::new(SupervisorConfig::default())
+    }
+}
+
+impl<const N: usize, const H: usize> SyncSupervisor<N, H> {
+    /// Creates a new [`SyncSupervisor`] with an empty history, given its configuration.
+    /// # Arguments
+    /// * `config` - The [`SupervisorConfig`] struct containing the configuration.
+    pub fn new(config: SupervisorConfig) -> Self {
+        Self {
+            config,
+            history: [[0; N]; H],
+            len: 0,
+            next: 0,
+            interval_ms: config
+                .initial_interval_ms
+                .clamp(config.min_interval_ms, config.max_interval_ms),
+            consecutive: [0; N],
+            alarms: [false; N],
+        }
+    }
+
+    /// Records the drift values returned by a synchronization adjustment, updating the interval
+    /// before the next adjustment and the alarms. When all the ADCs are within ±1 counts, the
+    /// interval is doubled. Otherwise, it is divided by the largest absolute drift, so that the
+    /// expected drift at the next adjustment is within ±1 counts. This method returns the alarms
+    /// of the ADCs.
+    /// # Arguments
+    /// * `drift` - The drift values of the ADCs in counts.
+    pub fn record(&mut self, drift: [i16; N]) -> [bool; N] {
+        if H > 0 {
+            self.history[self.next] = drift;
+            self.next = (self.next + 1) % H;
+            self.len = (self.len + 1).min(H);
+        }
+        let max_drift = drift.iter().map(|d| d.unsigned_abs()).max().unwrap_or(0);
+        self.interval_ms = if max_drift <= 1 {
+            self.interval_ms.saturating_mul(2)
+        } else {
+            self.interval_ms / max_drift as u32
+        }
+        .clamp(self.config.min_interval_ms, self.config.max_interval_ms);
+        for (i, d) in drift.iter().enumerate() {
+            if (-1..=1).contains(d) {
+                self.consecutive[i] = 0;
+            } else {
+                self.consecutive[i] = self.consecutive[i].saturating_add(1);
+                if self.consecutive[i] >= self.config.alarm_threshold {
+                    self.alarms[i] = true;
+                }
+            }
+        }
+        self.alarms
+    }
+
+    /// Returns the interval before the next synchronization adjustment in milliseconds.
+    pub fn interval_ms(&self) -> u32 {
+        self.interval_ms
+    }
+
+    /// Returns the alarms of the ADCs. An alarm is raised when an ADC drifts by more than ±1 counts
+    /// for a number of consecutive adjustments, and it stays raised until cleared with
+    /// [`Self::clear_alarms()`].
+    pub fn alarms(&self) -> [bool; N] {
+        self.alarms
+    }
+
+    /// Clears the alarms of the ADCs.
+    pub fn clear_alarms(&mut self) {
+        self.alarms = [false; N];
+        self.consecutive = [0; N];
+    }
+
+    /// Returns the drift statistics of the ADC at the given index over the recorded history.
+    /// # Arguments
+    /// * `index` - The index of the ADC.
+    pub fn stats(&self, index: usize) -> DriftStats {
+        if self.len == 0 {
+            return DriftStats::default();
+        }
+        let mut stats = DriftStats {
+            count: self.len,
+            min: i16::MAX,
+            max: i16::MIN,
+            ..Default::default()
+        };
+        let mut sum = 0_i64;
+        let mut sum_abs = 0_u64;
+        for drift in self.history[..self.len].iter().map(|d| d[index]) {
+            stats.min = stats.min.min(drift);
+            stats.max = stats.max.max(drift);
+            sum += drift as i64;
+            sum_abs += drift.unsigned_abs() as u64;
+            if !(-1..=1).contains(&drift) {
+                stats.excursions += 1;
+            }
+        }
+        stats.mean = (sum as f64 / self.len as f64) as f32;
+        stats.mean_abs = (sum_abs as f64 / self.len as f64) as f32;
+        stats
+    }
+
+    /// Returns the drift values in the history, from the oldest to the newest.
+    pub fn history(&self) -> impl Iterator<Item = &[i16; N]> {
+        let start = if self.len < H { 0 } else { self.next };
+        (0..self.len).map(move |k| &self.history[(start + k) % H])
+    }
+}
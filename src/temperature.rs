@@ -0,0 +1,347 @@
+use super::*;
+
+/// Gain of the internal temperature sensor in °C/LSB, when the bandwidth is 3.3 kHz (`bw = false`
+/// in [`Config`]).
+pub const TEMPERATURE_GAIN_3K3: f32 = 8.72101e-5;
+/// Gain of the internal temperature sensor in °C/LSB, when the bandwidth is 2 kHz (`bw = true` in
+/// [`Config`]).
+pub const TEMPERATURE_GAIN_2K: f32 = 8.21015e-5;
+/// Offset of the internal temperature sensor in °C.
+pub const TEMPERATURE_OFFSET: f32 = -306.47;
+
+/// Contains the calibration values of the internal temperature sensor. The temperature is computed
+/// from the V2WV register as described in the ADE7912/ADE7913 Datasheet:
+///
+/// `temperature = gain * (v2wv + tempos * 2048) + offset`
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TemperatureCalibration {
+    /// Gain of the sensor in °C/LSB.
+    pub gain: f32,
+    /// Offset of the sensor in °C.
+    pub offset: f32,
+    /// Offset correction of the sensor, as stored in the TEMPOS register during manufacturing.
+    pub tempos: i8,
+}
+
+impl TemperatureCalibration {
+    /// Creates a new [`TemperatureCalibration`] given the TEMPOS register value and the bandwidth
+    /// setting, using the gain and offset values from the ADE7912/ADE7913 Datasheet.
+    /// # Arguments
+    /// * `tempos` - The content of the TEMPOS register.
+    /// * `bw` - The bandwidth setting, as in the `bw` field of [`Config`].
+    pub fn from_tempos(tempos: i8, bw: bool) -> Self {
+        Self {
+            gain: if bw {
+                TEMPERATURE_GAIN_2K
+            } else {
+                TEMPERATURE_GAIN_3K3
+            },
+            offset: TEMPERATURE_OFFSET,
+            tempos,
+        }
+    }
+
+    /// Returns the conversion [`Coefficients`] equivalent to the calibration.
+    pub fn coefficients(&self) -> Coefficients {
+        Coefficients {
+            scale: self.gain,
+            offset: (self.gain as f64 * self.tempos as f64 * 2048.0 + self.offset as f64) as f32,
+        }
+    }
+
+    /// Returns the temperature in °C given the raw value of the V2WV register.
+    /// # Arguments
+    /// * `v2wv` - The raw value of the V2WV register.
+    pub fn convert(&self, v2wv: i32) -> f32 {
+        self.coefficients().apply(v2wv)
+    }
+}
+
+/// Contains the schedule of the interleaved temperature sampling for the ADE7913. When a schedule
+/// is active, the second voltage channel is periodically switched to the internal temperature
+/// sensor, so that the die temperature can be measured without giving up the second voltage
+/// measurement. All the durations are expressed in number of ADC output samples.
+///
+/// Each switch of the channel is performed while reading the measurement that triggers it, and
+/// costs a CONFIG writing with its read-back, preceded by an unlock and followed by a lock and a
+/// STATUS0 reading when the protection is enabled, i.e. up to 5 additional SPI transactions. When
+/// the measurements are read in the DREADY ISR, this cost must fit in the output period twice
+/// every temperature window.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TemperatureSchedule {
+    /// Number of voltage samples between two temperature windows.
+    pub interval: u32,
+    /// Number of samples discarded after each switch of the second voltage channel, while the
+    /// digital filter settles.
+    pub settling: u16,
+    /// Number of temperature samples averaged in each temperature window.
+    pub window: u16,
+}
+
+impl TemperatureSchedule {
+    /// Creates a new [`TemperatureSchedule`] measuring the temperature once every `period_s`
+    /// seconds, given the ADC output frequency. The temperature is averaged over 1 ms worth of
+    /// samples, after discarding 1 ms worth of samples for settling. The interval saturates at
+    /// [`u32::MAX`] samples for very long periods.
+    /// # Arguments
+    /// * `adc_freq` - The ADC output frequency as an [`AdcFreqVal`].
+    /// * `period_s` - The period between two temperature windows in seconds.
+    pub fn every(adc_freq: AdcFreqVal, period_s: u32) -> Self {
+        let samples_ms = (adc_freq.hz() / 1000) as u16;
+        Self {
+            interval: adc_freq.hz().saturating_mul(period_s),
+            settling: samples_ms,
+            window: samples_ms,
+        }
+    }
+}
+
+/// Represents the phases of the interleaved temperature sampling.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Phase {
+    /// Measuring the voltage, with the number of samples left before switching to temperature.
+    Voltage(u32),
+    /// Settling after switching to temperature, with the number of samples left to discard.
+    TemperatureSettling(u16),
+    /// Measuring the temperature, with the number of samples left in the window.
+    Temperature(u16),
+    /// Settling after switching back to voltage, with the number of samples left to discard.
+    VoltageSettling(u16),
+}
+
+/// Represents the switches of the second voltage channel requested by the scheduler.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Switch {
+    None,
+    ToTemperature,
+    ToVoltage,
+}
+
+/// Internal state machine driving the interleaved temperature sampling.
+#[derive(Clone)]
+pub(crate) struct TemperatureScheduler {
+    schedule: TemperatureSchedule,
+    phase: Phase,
+    sum: i64,
+    temperature: Option<f32>,
+}
+
+impl TemperatureScheduler {
+    /// Creates a new [`TemperatureScheduler`] starting with the voltage measurement.
+    /// # Arguments
+    /// * `schedule` - The [`TemperatureSchedule`] to follow.
+    pub fn new(schedule: TemperatureSchedule) -> Self {
+        Self {
+            schedule,
+            phase: Phase::Voltage(schedule.interval),
+            sum: 0,
+            temperature: None,
+        }
+    }
+
+    /// Restarts the schedule from the voltage measurement, discarding the settling samples. This
+    /// method must be called when the voltage channel is restored outside of the scheduler.
+    pub fn restart(&mut self) {
+        self.sum = 0;
+        self.phase = self.voltage_settling();
+    }
+
+    /// Advances the schedule by one sample. This method returns `true` if the given sample is a
+    /// valid voltage sample, and the switch of the channel to perform before the next sample.
+    /// # Arguments
+    /// * `v2wv` - The raw value of the V2WV register.
+    /// * `coefficients` - The temperature conversion [`Coefficients`].
+    pub fn step(&mut self, v2wv: i32, coefficients: &Coefficients) -> (bool, Switch) {
+        match self.phase {
+            Phase::Voltage(n) if n > 1 => {
+                self.phase = Phase::Voltage(n - 1);
+                (true, Switch::None)
+            }
+            Phase::Voltage(_) => {
+                self.phase = if self.schedule.settling > 0 {
+                    Phase::TemperatureSettling(self.schedule.settling)
+                } else {
+                    Phase::Temperature(self.schedule.window.max(1))
+                };
+                (true, Switch::ToTemperature)
+            }
+            Phase::TemperatureSettling(n) => {
+                self.phase = if n > 1 {
+                    Phase::TemperatureSettling(n - 1)
+                } else {
+                    Phase::Temperature(self.schedule.window.max(1))
+                };
+                (false, Switch::None)
+            }
+            Phase::Temperature(n) => {
+                self.sum += v2wv as i64;
+                if n > 1 {
+                    self.phase = Phase::Temperature(n - 1);
+                    return (false, Switch::None);
+                }
+                let average = self.sum as f64 / self.schedule.window.max(1) as f64;
+                self.temperature = Some(coefficients.scale * average as f32 + coefficients.offset);
+                self.sum = 0;
+                self.phase = self.voltage_settling();
+                (false, Switch::ToVoltage)
+            }
+            Phase::VoltageSettling(n) => {
+                self.phase = if n > 1 {
+                    Phase::VoltageSettling(n - 1)
+                } else {
+                    Phase::Voltage(self.schedule.interval)
+                };
+                (false, Switch::None)
+            }
+        }
+    }
+
+    /// Returns the latest temperature measured by the scheduler, if any, without consuming it.
+    pub fn peek(&self) -> Option<f32> {
+        self.temperature
+    }
+
+    /// Returns `true` if the second voltage channel is currently switched to the temperature
+    /// sensor by the scheduler, `false` otherwise.
+    pub fn temp_en(&self) -> bool {
+        matches!(
+            self.phase,
+            Phase::TemperatureSettling(_) | Phase::Temperature(_)
+        )
+    }
+
+    /// Returns the latest temperature measured by the scheduler, if any, leaving [`None`] in its
+    /// place.
+    pub fn take(&mut self) -> Option<f32> {
+        self.temperature.take()
+    }
+
+    /// Returns the phase to enter when switching back to the voltage measurement.
+    fn voltage_settling(&self) -> Phase {
+        if self.schedule.settling > 0 {
+            Phase::VoltageSettling(self.schedule.settling)
+        } else {
+            Phase::Voltage(self.schedule.interval)
+        }
+    }
+}
+
+/// Number of points of a [`TempCo::Table`].
+pub const TEMPCO_TABLE_LEN: usize = 4;
+
+/// Represents the temperature coefficient of a channel gain. The coefficient is expressed as a
+/// correction factor, depending on the die temperature, that multiplies the calibrated
+/// measurement.
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TempCo {
+    /// No temperature compensation.
+    #[default]
+    None,
+    /// Linear compensation. The correction factor is `1 + k * (temperature - t0)`.
+    Linear {
+        /// Reference temperature in °C, at which the correction factor is `1`.
+        t0: f32,
+        /// Relative gain variation in 1/°C.
+        k: f32,
+    },
+    /// Lookup table compensation. The table contains `(temperature, factor)` points sorted by
+    /// temperature in °C. The correction factor is linearly interpolated between the points and
+    /// clamped outside of them.
+    Table([(f32, f32); TEMPCO_TABLE_LEN]),
+}
+
+impl TempCo {
+    /// Returns the correction factor at the given temperature.
+    /// # Arguments
+    /// * `temperature` - The die temperature in °C.
+    pub fn factor(&self, temperature: f32) -> f32 {
+        match self {
+            TempCo::None => 1.0,
+            TempCo::Linear { t0, k } => 1.0 + k * (temperature - t0),
+            TempCo::Table(table) => interpolate(table, temperature),
+        }
+    }
+
+    /// Fits a linear temperature coefficient, using the least squares method, from the gains
+    /// calibrated at two or more temperatures. This method returns the gain at the reference
+    /// temperature, to be used as calibration gain, together with the fitted [`TempCo::Linear`].
+    /// If less than two distinct temperatures are given, [`None`] is returned.
+    /// # Arguments
+    /// * `points` - The `(temperature, gain)` points, with the temperatures in °C.
+    /// * `t0` - The reference temperature in °C.
+    pub fn fit_linear(points: &[(f32, f32)], t0: f32) -> Option<(f32, Self)> {
+        if points.len() < 2 {
+            return None;
+        }
+        let n = points.len() as f64;
+        let mean_t = points.iter().map(|p| p.0 as f64).sum::<f64>() / n;
+        let mean_g = points.iter().map(|p| p.1 as f64).sum::<f64>() / n;
+        let var_t: f64 = points
+            .iter()
+            .map(|p| (p.0 as f64 - mean_t) * (p.0 as f64 - mean_t))
+            .sum();
+        if var_t == 0.0 {
+            return None;
+        }
+        let cov: f64 = points
+            .iter()
+            .map(|p| (p.0 as f64 - mean_t) * (p.1 as f64 - mean_g))
+            .sum();
+        let slope = cov / var_t;
+        let gain = mean_g + slope * (t0 as f64 - mean_t);
+        Some((
+            gain as f32,
+            TempCo::Linear {
+                t0,
+                k: (slope / gain) as f32,
+            },
+        ))
+    }
+
+    /// Fits a lookup table temperature coefficient from the gains calibrated at two or more, up
+    /// to [`TEMPCO_TABLE_LEN`], temperatures. This method returns the gain at the reference
+    /// temperature, to be used as calibration gain, together with the fitted [`TempCo::Table`].
+    /// If less than two or more than [`TEMPCO_TABLE_LEN`] points are given, or if the
+    /// temperatures are not distinct, [`None`] is returned.
+    /// # Arguments
+    /// * `points` - The `(temperature, gain)` points, with the temperatures in °C.
+    /// * `t0` - The reference temperature in °C.
+    pub fn fit_table(points: &[(f32, f32)], t0: f32) -> Option<(f32, Self)> {
+        if points.len() < 2 || points.len() > TEMPCO_TABLE_LEN {
+            return None;
+        }
+        let mut table = [(0.0, 0.0); TEMPCO_TABLE_LEN];
+        table[..points.len()].copy_from_slice(points);
+        table[..points.len()].sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+        if table[..points.len()].windows(2).any(|w| w[0].0 == w[1].0) {
+            return None;
+        }
+        let last = table[points.len() - 1];
+        table[points.len()..].fill(last);
+        let gain = interpolate(&table, t0);
+        for point in &mut table {
+            point.1 /= gain;
+        }
+        Some((gain, TempCo::Table(table)))
+    }
+}
+
+/// Returns the value linearly interpolated from the given sorted table of `(x, y)` points, clamped
+/// outside of them.
+fn interpolate(table: &[(f32, f32)], x: f32) -> f32 {
+    if x <= table[0].0 {
+        return table[0].1;
+    }
+    for w in table.windows(2) {
+        if x <= w[1].0 {
+            return w[0].1 + (w[1].1 - w[0].1) * (x - w[0].0) / (w[1].0 - w[0].0);
+        }
+    }
+    table[table.len() - 1].1
+}
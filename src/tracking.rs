@@ -0,0 +1,119 @@
+//! Sequencing of the samples coming from the ADCs.
+//!
+//! The ADCs do not number their outputs: the CNT_SNAPSHOT register latches the internal counter,
+//! which wraps around at every output period, so it can be used to check the alignment of the ADCs
+//! but not to tell consecutive samples apart. The [`SampleTracker`] derives a monotonically
+//! increasing sample index from the ADC output frequency and a timestamp taken from a
+//! user-supplied monotonic clock when the sample is read, detecting missed samples and duplicate
+//! reads.
+
+use super::AdcFreqVal;
+
+/// Contains a value read from the ADCs, stamped with its sequence information.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tracked<T> {
+    /// The value read from the ADCs.
+    pub value: T,
+    /// Index of the sample, counted in ADC output periods since the first tracked sample.
+    pub index: u64,
+    /// Timestamp of the read in µs, as provided by the user.
+    pub timestamp_us: u64,
+    /// Number of samples missed between the previous read and this one.
+    pub missed: u32,
+    /// `true` if the read returned the same sample as the previous one, `false` otherwise.
+    pub duplicate: bool,
+}
+
+impl<T> Tracked<T> {
+    /// Maps the value of a [`Tracked`] struct, keeping its sequence information.
+    /// # Arguments
+    /// * `f` - The function applied to the value.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Tracked<U> {
+        Tracked {
+            value: f(self.value),
+            index: self.index,
+            timestamp_us: self.timestamp_us,
+            missed: self.missed,
+            duplicate: self.duplicate,
+        }
+    }
+}
+
+/// Tracks the sequence of the samples read from the ADCs. The number of output periods elapsed
+/// between two reads is estimated from their timestamps, so the read latency after the DREADY
+/// falling edge must vary by less than half an output period. A read is considered a duplicate if
+/// it is taken less than half an output period after the previous one, meaning that no new output
+/// was available. The data is not compared, as consecutive outputs may hold the same values, e.g.
+/// with a constant input.
+#[derive(Debug, Clone)]
+pub struct SampleTracker {
+    period_us: u64,
+    index: u64,
+    last_us: Option<u64>,
+    missed: u64,
+    duplicates: u64,
+}
+
+impl SampleTracker {
+    /// Creates a new [`SampleTracker`], given the ADC output frequency.
+    /// # Arguments
+    /// * `adc_freq` - The ADC output frequency as a [`AdcFreqVal`].
+    pub fn new(adc_freq: AdcFreqVal) -> Self {
+        Self {
+            period_us: 1_000_000 / adc_freq.hz() as u64,
+            index: 0,
+            last_us: None,
+            missed: 0,
+            duplicates: 0,
+        }
+    }
+
+    /// Restarts the tracking, so that the next tracked sample has index zero.
+    /// # Arguments
+    /// * `adc_freq` - The ADC output frequency as a [`AdcFreqVal`].
+    pub fn reset(&mut self, adc_freq: AdcFreqVal) {
+        *self = Self::new(adc_freq);
+    }
+
+    /// Stamps the given value with its sequence information.
+    /// # Arguments
+    /// * `timestamp_us` - The timestamp of the read in µs, taken from a monotonic clock.
+    /// * `value` - The value read from the ADCs.
+    pub fn track<T>(&mut self, timestamp_us: u64, value: T) -> Tracked<T> {
+        let mut tracked = Tracked {
+            value,
+            index: 0,
+            timestamp_us,
+            missed: 0,
+            duplicate: false,
+        };
+        if let Some(last_us) = self.last_us {
+            let elapsed = timestamp_us.saturating_sub(last_us);
+            let periods = (elapsed + self.period_us / 2) / self.period_us;
+            if periods == 0 {
+                tracked.duplicate = true;
+                tracked.index = self.index;
+                self.duplicates += 1;
+                return tracked;
+            }
+            tracked.missed = (periods - 1).min(u32::MAX as u64) as u32;
+            self.index += periods;
+            self.missed += periods - 1;
+        }
+        tracked.index = self.index;
+        self.last_us = Some(timestamp_us);
+        tracked
+    }
+
+    /// Returns the total number of missed samples since the tracking started.
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+
+    /// Returns the total number of duplicate reads since the tracking started.
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+}
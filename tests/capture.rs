@@ -0,0 +1,142 @@
+use ade791x::capture::*;
+use ade791x::*;
+
+fn raw(iwv: i32, v1wv: i32) -> RawMeasurement {
+    RawMeasurement {
+        iwv,
+        v1wv,
+        v2wv: 1000,
+    }
+}
+
+#[test]
+fn current_above() {
+    let conversion =
+        [Conversion::new(Chip::ADE7912, &Config::default(), &Calibration::default()); 2];
+    let config = CaptureConfig {
+        pre: 2,
+        post: 3,
+        trigger: Trigger::CurrentAbove(10.0),
+        channels: [Channels::Iwv, Channels::All],
+    };
+    let mut capture = Capture::<2, 6>::new(config, conversion);
+    for k in 1..=4 {
+        assert_eq!(capture.push(&[raw(k, k), raw(k, k)]), CaptureState::Armed);
+    }
+    assert_eq!(capture.trigger_position(), None);
+    assert_eq!(
+        capture.push(&[raw(5, 5), raw(-2_000_000, 5)]),
+        CaptureState::Triggered
+    );
+    assert_eq!(
+        capture.push(&[raw(6, 6), raw(6, 6)]),
+        CaptureState::Triggered
+    );
+    assert_eq!(capture.push(&[raw(7, 7), raw(7, 7)]), CaptureState::Frozen);
+    assert_eq!(capture.push(&[raw(8, 8), raw(8, 8)]), CaptureState::Frozen);
+    assert_eq!(capture.trigger_position(), Some(2));
+    assert_eq!(
+        capture.frames().copied().collect::<Vec<_>>(),
+        [
+            [
+                RawMeasurement {
+                    iwv: 3,
+                    v1wv: 0,
+                    v2wv: 0
+                },
+                raw(3, 3)
+            ],
+            [
+                RawMeasurement {
+                    iwv: 4,
+                    v1wv: 0,
+                    v2wv: 0
+                },
+                raw(4, 4)
+            ],
+            [
+                RawMeasurement {
+                    iwv: 5,
+                    v1wv: 0,
+                    v2wv: 0
+                },
+                raw(-2_000_000, 5)
+            ],
+            [
+                RawMeasurement {
+                    iwv: 6,
+                    v1wv: 0,
+                    v2wv: 0
+                },
+                raw(6, 6)
+            ],
+            [
+                RawMeasurement {
+                    iwv: 7,
+                    v1wv: 0,
+                    v2wv: 0
+                },
+                raw(7, 7)
+            ],
+        ]
+    );
+    assert_eq!(capture.measurements().count(), 5);
+    capture.rearm();
+    assert_eq!(capture.state(), CaptureState::Armed);
+    assert_eq!(capture.frames().count(), 0);
+}
+
+#[test]
+fn voltage_dip() {
+    let conversion = [Conversion::new(
+        Chip::ADE7913,
+        &Config::default(),
+        &Calibration::default(),
+    )];
+    let config = CaptureConfig {
+        pre: 8,
+        post: 1,
+        trigger: Trigger::VoltageDip {
+            threshold: 100.0,
+            window: 2,
+        },
+        channels: [Channels::IwvV1wv],
+    };
+    let mut capture = Capture::<1, 4>::new(config, conversion);
+    assert_eq!(capture.config().pre, 4);
+    assert_eq!(capture.config().post, 0);
+    for v1wv in [2_000_000, -2_000_000, 2_000_000, 500_000, 100_000] {
+        assert_eq!(capture.push(&[raw(0, v1wv)]), CaptureState::Armed);
+    }
+    assert_eq!(capture.push(&[raw(0, -100_000)]), CaptureState::Frozen);
+    assert_eq!(capture.trigger_position(), Some(4));
+    assert_eq!(
+        capture.frames().map(|f| f[0].v1wv).collect::<Vec<_>>(),
+        [-2_000_000, 2_000_000, 500_000, 100_000]
+    );
+}
+
+#[test]
+fn manual() {
+    let conversion = [Conversion::new(
+        Chip::ADE7912,
+        &Config::default(),
+        &Calibration::default(),
+    )];
+    let config = CaptureConfig {
+        pre: 1,
+        post: 2,
+        trigger: Trigger::Manual,
+        channels: [Channels::All],
+    };
+    let mut capture = Capture::<1, 3>::new(config, conversion);
+    assert_eq!(capture.push(&[raw(1, 1)]), CaptureState::Armed);
+    assert_eq!(capture.push(&[raw(2, 2)]), CaptureState::Armed);
+    capture.trigger();
+    assert_eq!(capture.push(&[raw(3, 3)]), CaptureState::Triggered);
+    assert_eq!(capture.push(&[raw(4, 4)]), CaptureState::Frozen);
+    assert_eq!(
+        capture.frames().copied().collect::<Vec<_>>(),
+        [[raw(2, 2)], [raw(3, 3)], [raw(4, 4)]]
+    );
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "cli")]
+
+use std::path::PathBuf;
+use std::process::Command;
+
+fn board(name: &str, content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("ade791x-{}-{name}.toml", std::process::id()));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+fn run(board: &PathBuf, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_ade791x"))
+        .arg("--sim")
+        .arg("--board")
+        .arg(board)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn single() {
+    let board = board("single", "[[device]]\nchip = \"ADE7912\"\n");
+
+    let stdout = run(&board, &["init"]);
+    assert_eq!(
+        stdout,
+        "adc 0: ADE7912, clock Crystal, emi_ctrl 0xff, dready\n"
+    );
+
+    let stdout = run(&board, &["stream", "--count", "3"]);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0], "index,current0,voltage0,aux0");
+    assert!(lines[3].starts_with("2,"));
+
+    let stdout = run(&board, &["stream", "--count", "2", "--format", "json"]);
+    for (k, line) in stdout.lines().enumerate() {
+        let value: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(value["index"], k);
+        assert_eq!(value["adc"][0]["aux_temperature"], true);
+    }
+}
+
+#[test]
+fn poly() {
+    let board = board(
+        "poly",
+        "[[device]]\nchip = \"ADE7913\"\nclkout_en = true\nadc_freq = 4000\n\n\
+         [[device]]\nchip = \"ADE7913\"\nadc_freq = 4000\n",
+    );
+
+    let stdout = run(&board, &["init"]);
+    assert_eq!(
+        stdout,
+        "adc 0: ADE7913, clock Crystal, emi_ctrl 0x55\n\
+         adc 1: ADE7913, clock Clkout(0), emi_ctrl 0xaa, dready\n"
+    );
+
+    let stdout = run(&board, &["dump"]);
+    assert_eq!(stdout.matches("RegisterDump").count(), 2);
+    assert!(!stdout.contains("unexpected content"));
+
+    let stdout = run(&board, &["sync", "--count", "2", "--interval-ms", "1"]);
+    assert!(stdout.starts_with("0: drift [0, 0], alarms [false, false]\n"));
+
+    let stdout = run(&board, &["calibrate", "offset", "--samples", "80"]);
+    assert_eq!(stdout.matches("offset_aux").count(), 2);
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "std")]
+
+use ade791x::capture::*;
+use ade791x::comtrade::*;
+use ade791x::*;
+
+fn capture() -> Capture<2, 2> {
+    let conversion = [
+        Conversion::new(Chip::ADE7912, &Config::default(), &Calibration::default()),
+        Conversion::new(Chip::ADE7913, &Config::default(), &Calibration::default()),
+    ];
+    let config = CaptureConfig {
+        pre: 1,
+        post: 1,
+        trigger: Trigger::Manual,
+        channels: [Channels::Iwv, Channels::All],
+    };
+    let mut capture = Capture::new(config, conversion);
+    let raw = |x| RawMeasurement {
+        iwv: x,
+        v1wv: -x,
+        v2wv: 2 * x,
+    };
+    capture.push(&[raw(1), raw(2)]);
+    capture.trigger();
+    capture.push(&[raw(3), raw(-4)]);
+    capture
+}
+
+const HEADER: Header = Header {
+    station_name: "Station",
+    device_id: "Meter",
+    line_frequency: 50.0,
+    adc_freq: AdcFreqVal::KHz8,
+    trigger_time_us: 1_700_000_000_000_125,
+};
+
+#[test]
+fn cfg() {
+    let mut cfg = Vec::new();
+    write_cfg(&mut cfg, &HEADER, &capture(), Format::Ascii).unwrap();
+    assert_eq!(
+        String::from_utf8(cfg).unwrap(),
+        "Station,Meter,2013\r\n\
+         4,4A,0D\r\n\
+         1,IA,A,,A,5.873442e-6,2.936721e-6,0,-8388608,8388607,1,1,P\r\n\
+         2,IB,B,,A,5.873442e-6,2.936721e-6,0,-8388608,8388607,1,1,P\r\n\
+         3,VB,B,,V,9.393693e-5,4.6968464e-5,0,-8388608,8388607,1,1,P\r\n\
+         4,V2B,B,,V,9.393693e-5,4.6968464e-5,0,-8388608,8388607,1,1,P\r\n\
+         50\r\n\
+         1\r\n\
+         8000,2\r\n\
+         14/11/2023,22:13:20.000000\r\n\
+         14/11/2023,22:13:20.000125\r\n\
+         ASCII\r\n\
+         1\r\n\
+         0,0\r\n\
+         0,0\r\n"
+    );
+}
+
+#[test]
+fn dat() {
+    let mut dat = Vec::new();
+    write_dat(&mut dat, &HEADER, &capture(), Format::Ascii).unwrap();
+    assert_eq!(
+        String::from_utf8(dat).unwrap(),
+        "1,0,1,2,-2,4\r\n2,125,3,-4,4,-8\r\n"
+    );
+    let mut dat = Vec::new();
+    write_dat(&mut dat, &HEADER, &capture(), Format::Binary32).unwrap();
+    let words: Vec<i32> = dat
+        .chunks(4)
+        .map(|c| i32::from_le_bytes(c.try_into().unwrap()))
+        .collect();
+    assert_eq!(words, [1, 0, 1, 2, -2, 4, 2, 125, 3, -4, 4, -8]);
+}
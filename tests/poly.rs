@@ -38,6 +38,10 @@ fn init() {
         SpiTransaction::transfer(vec![0x58, 0x01], vec![0x58, 0x01]),
         // Write LOCK (lock enable)
         SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Read STATUS0 (lock check)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
     ];
     let cs_expectations = [
         PinTransaction::set(PinState::Low),
@@ -56,6 +60,8 @@ fn init() {
         PinTransaction::set(PinState::High),
         PinTransaction::set(PinState::Low),
         PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
     ];
     let spi = SpiMock::new(&spi_expectations);
     let cs0 = PinMock::new(&cs_expectations);
@@ -74,11 +80,6 @@ fn init() {
         Config::default(),
     ];
     let calibration = [Calibration::default(); 3];
-    let emi_ctrl = [
-        EmiCtrl::from(0x55),
-        EmiCtrl::from(0xAA),
-        EmiCtrl::from(0x55),
-    ];
     let mut adc = poly::Ade791x::new(
         spi,
         [
@@ -87,7 +88,13 @@ fn init() {
             (cs2, Chip::ADE7912),
         ],
     );
+    let emi_ctrl = [
+        EmiCtrl::from(0x55),
+        EmiCtrl::from(0xAA),
+        EmiCtrl::from(0x55),
+    ];
     adc.init(&mut delay, config, calibration, emi_ctrl).unwrap();
+    assert_eq!(adc.topology(), poly::Topology::chain());
 }
 
 #[test]
@@ -99,6 +106,8 @@ fn init_timeout() {
         SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x01]),
         SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x01]),
         SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x01]),
+        // Write LOCK (lock enable)
+        SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
     ];
     let cs_expectations = [
         PinTransaction::set(PinState::Low),
@@ -111,15 +120,89 @@ fn init_timeout() {
         PinTransaction::set(PinState::High),
         PinTransaction::set(PinState::Low),
         PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
     ];
     let spi = SpiMock::new(&spi_expectations);
     let cs0 = PinMock::new(&cs_expectations);
+    let cs1 = PinMock::new(&cs_expectations[..2]);
+    let cs2 = PinMock::new(&cs_expectations[..2]);
+    let mut delay = MockNoop::new();
+    let config = [
+        Config {
+            clkout_en: true,
+            ..Default::default()
+        },
+        Config {
+            clkout_en: true,
+            ..Default::default()
+        },
+        Config::default(),
+    ];
+    let calibration = [Calibration::default(); 3];
+    let mut adc = poly::Ade791x::new(
+        spi,
+        [
+            (cs0, Chip::ADE7912),
+            (cs1, Chip::ADE7912),
+            (cs2, Chip::ADE7912),
+        ],
+    );
+    let emi_ctrl = [EmiCtrl::default(); 3];
+    assert_eq!(
+        adc.init(&mut delay, config, calibration, emi_ctrl),
+        Err(Error::ResetTimeout { device: 0 })
+    );
+}
+
+#[test]
+fn init_emi_ctrl_invalid() {
+    let spi = SpiMock::new(&[]);
+    let cs0 = PinMock::new(&[]);
+    let cs1 = PinMock::new(&[]);
+    let mut delay = MockNoop::new();
+    let config = [
+        Config {
+            clkout_en: true,
+            ..Default::default()
+        },
+        Config::default(),
+    ];
+    let calibration = [Calibration::default(); 2];
+    let mut adc = poly::Ade791x::new(spi, [(cs0, Chip::ADE7912), (cs1, Chip::ADE7912)]);
+    assert_eq!(
+        adc.init(
+            &mut delay,
+            config,
+            calibration,
+            [EmiCtrl::from(0x55), EmiCtrl::from(0x00)]
+        ),
+        Err(Error::EmiCtrlEmpty { device: 1 })
+    );
+    adc.set_strict_emi_ctrl(true);
+    assert_eq!(
+        adc.init(
+            &mut delay,
+            config,
+            calibration,
+            [EmiCtrl::from(0x55), EmiCtrl::from(0x5A)]
+        ),
+        Err(Error::EmiCtrlOverlap { device: 1 })
+    );
+}
+
+#[test]
+fn init_topology_invalid() {
+    let spi = SpiMock::new(&[]);
+    let cs0 = PinMock::new(&[]);
     let cs1 = PinMock::new(&[]);
     let cs2 = PinMock::new(&[]);
     let mut delay = MockNoop::new();
-    let config = [Config::default(); 3];
+    let clkout = Config {
+        clkout_en: true,
+        ..Default::default()
+    };
     let calibration = [Calibration::default(); 3];
-    let emi_ctrl = [EmiCtrl::default(); 3];
     let mut adc = poly::Ade791x::new(
         spi,
         [
@@ -128,9 +211,147 @@ fn init_timeout() {
             (cs2, Chip::ADE7912),
         ],
     );
+    let emi_ctrl = adc.recommended_emi_ctrl();
+    let invalid = [
+        ([clkout; 3], poly::TopologyError::NoDreadySource),
+        (
+            [clkout, Config::default(), Config::default()],
+            poly::TopologyError::MultipleDreadySources,
+        ),
+        (
+            [
+                clkout,
+                Config {
+                    adc_freq: AdcFreqVal::KHz4,
+                    ..clkout
+                },
+                Config::default(),
+            ],
+            poly::TopologyError::AdcFreqMismatch,
+        ),
+        (
+            [
+                clkout,
+                clkout,
+                Config {
+                    bw: true,
+                    ..Default::default()
+                },
+            ],
+            poly::TopologyError::BwMismatch,
+        ),
+    ];
+    for (config, error) in invalid {
+        assert_eq!(
+            adc.init(&mut delay, config, calibration, emi_ctrl),
+            Err(Error::Topology(error))
+        );
+    }
+    let config = [clkout, clkout, Config::default()];
+    adc.set_topology(poly::Topology {
+        clock: [
+            poly::ClockSource::Crystal,
+            poly::ClockSource::Crystal,
+            poly::ClockSource::Clkout(1),
+        ],
+        dready: 0,
+    })
+    .unwrap();
     assert_eq!(
         adc.init(&mut delay, config, calibration, emi_ctrl),
-        Err(Error::ResetTimeout)
+        Err(Error::Topology(poly::TopologyError::DreadyMismatch(0)))
+    );
+    let invalid = [
+        (
+            [
+                poly::ClockSource::Crystal,
+                poly::ClockSource::Clkout(0),
+                poly::ClockSource::Clkout(1),
+            ],
+            3,
+            poly::TopologyError::InvalidIndex(3),
+        ),
+        (
+            [
+                poly::ClockSource::Crystal,
+                poly::ClockSource::Clkout(5),
+                poly::ClockSource::Clkout(1),
+            ],
+            2,
+            poly::TopologyError::InvalidIndex(5),
+        ),
+        (
+            [
+                poly::ClockSource::Crystal,
+                poly::ClockSource::Clkout(2),
+                poly::ClockSource::Clkout(0),
+            ],
+            2,
+            poly::TopologyError::DreadyClkout(2),
+        ),
+        (
+            [
+                poly::ClockSource::Clkout(1),
+                poly::ClockSource::Clkout(0),
+                poly::ClockSource::Clkout(0),
+            ],
+            2,
+            poly::TopologyError::ClockLoop(0),
+        ),
+    ];
+    for (clock, dready, error) in invalid {
+        assert_eq!(
+            adc.set_topology(poly::Topology { clock, dready }),
+            Err(error)
+        );
+    }
+}
+
+#[test]
+fn set_topology_single() {
+    let spi = SpiMock::new(&[]);
+    let cs0 = PinMock::new(&[]);
+    let mut adc = poly::Ade791x::new(spi, [(cs0, Chip::ADE7912)]);
+    assert_eq!(
+        adc.set_topology(poly::Topology {
+            clock: [poly::ClockSource::Crystal],
+            dready: 1,
+        }),
+        Err(poly::TopologyError::InvalidIndex(1))
+    );
+    assert_eq!(adc.topology().dready, 0);
+}
+
+#[test]
+fn recommended_emi_ctrl() {
+    assert_eq!(
+        EmiCtrl::allocate([Chip::ADE7912, Chip::ADE7913, Chip::ADE7912]),
+        [
+            EmiCtrl::from(0x49),
+            EmiCtrl::from(0x92),
+            EmiCtrl::from(0x24),
+        ]
+    );
+    assert_eq!(
+        EmiCtrl::allocate([Chip::ADE7912, Chip::ADE7912]),
+        [EmiCtrl::from(0x55), EmiCtrl::from(0xAA)]
+    );
+    assert_eq!(
+        EmiCtrl::allocate([Chip::ADE7912, Chip::ADE7912, Chip::ADE7913]),
+        [
+            EmiCtrl::from(0x49),
+            EmiCtrl::from(0x12),
+            EmiCtrl::from(0xA4)
+        ]
+    );
+    assert_eq!(
+        EmiCtrl::allocate([Chip::ADE7913; 4]),
+        [
+            EmiCtrl::from(0x11),
+            EmiCtrl::from(0x22),
+            EmiCtrl::from(0x44),
+            EmiCtrl::from(0x88)
+        ]
     );
 }
 
@@ -201,6 +422,10 @@ fn powerdown() {
         SpiTransaction::transfer(vec![0x40, 0x04], vec![0x40, 0x04]),
         // Write LOCK (lock enable)
         SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Read STATUS0 (lock check)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
     ];
     let cs_expectations = [
         PinTransaction::set(PinState::Low),
@@ -209,6 +434,8 @@ fn powerdown() {
         PinTransaction::set(PinState::High),
         PinTransaction::set(PinState::Low),
         PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
     ];
     let spi = SpiMock::new(&spi_expectations);
     let cs0 = PinMock::new(&cs_expectations);
@@ -236,6 +463,10 @@ fn wakeup() {
         SpiTransaction::transfer(vec![0x40, 0x00], vec![0x40, 0x00]),
         // Write LOCK (lock enable)
         SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Read STATUS0 (lock check)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
     ];
     let cs_expectations = [
         PinTransaction::set(PinState::Low),
@@ -244,6 +475,8 @@ fn wakeup() {
         PinTransaction::set(PinState::High),
         PinTransaction::set(PinState::Low),
         PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
     ];
     let spi = SpiMock::new(&spi_expectations);
     let cs0 = PinMock::new(&cs_expectations);
@@ -277,6 +510,10 @@ fn adjust_sync() {
         SpiTransaction::transfer(vec![0x68, 0x01], vec![0x68, 0x01]),
         // Write LOCK (lock enable)
         SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Read STATUS0 (lock check)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
     ];
     let cs_expectations = [
         PinTransaction::set(PinState::Low),
@@ -291,6 +528,8 @@ fn adjust_sync() {
         PinTransaction::set(PinState::High),
         PinTransaction::set(PinState::Low),
         PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
     ];
     let spi = SpiMock::new(&spi_expectations);
     let cs0 = PinMock::new(&cs_expectations);
@@ -307,6 +546,376 @@ fn adjust_sync() {
     assert_eq!(adc.adjust_sync().unwrap(), [0, 1, -327]);
 }
 
+#[test]
+fn adjust_sync_topology() {
+    let spi_expectations = [
+        // Write LOCK (lock disable)
+        SpiTransaction::transfer(vec![0x50, 0x9C], vec![0x50, 0x9C]),
+        // Write SYNC_SNAP (snap trigger)
+        SpiTransaction::transfer(vec![0x58, 0x02], vec![0x58, 0x02]),
+        // Burst Read (CNT_SNAPSHOT only)
+        SpiTransaction::transfer(vec![0x3C, 0x00, 0x00], vec![0x3C, 0x01, 0x72]),
+        SpiTransaction::transfer(vec![0x3C, 0x00, 0x00], vec![0x3C, 0x01, 0x71]),
+        SpiTransaction::transfer(vec![0x3C, 0x00, 0x00], vec![0x3C, 0x00, 0x2A]),
+        // Write COUNTER0 (adjust sync)
+        SpiTransaction::transfer(vec![0x60, 0x48], vec![0x60, 0x48]),
+        // Write COUNTER1 (adjust sync)
+        SpiTransaction::transfer(vec![0x68, 0x01], vec![0x68, 0x01]),
+        // Write LOCK (lock enable)
+        SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Read STATUS0 (lock check)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+    ];
+    let cs_expectations = [
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ];
+    let spi = SpiMock::new(&spi_expectations);
+    let cs0 = PinMock::new(&cs_expectations);
+    let cs1 = PinMock::new(&cs_expectations);
+    let cs2 = PinMock::new(&cs_expectations);
+    let mut adc = poly::Ade791x::new(
+        spi,
+        [
+            (cs0, Chip::ADE7912),
+            (cs1, Chip::ADE7912),
+            (cs2, Chip::ADE7912),
+        ],
+    );
+    adc.set_topology(poly::Topology {
+        clock: [
+            poly::ClockSource::Clkout(2),
+            poly::ClockSource::Clkout(2),
+            poly::ClockSource::Crystal,
+        ],
+        dready: 1,
+    })
+    .unwrap();
+    assert_eq!(adc.adjust_sync().unwrap(), [-1, 0, -328]);
+}
+
+#[test]
+fn configure() {
+    let spi_expectations = [
+        // Write LOCK (lock disable)
+        SpiTransaction::transfer(vec![0x50, 0x9C], vec![0x50, 0x9C]),
+        // Write/Read EMI_CTRL (checked write)
+        SpiTransaction::transfer(vec![0x70, 0x0F], vec![0x70, 0x0F]),
+        SpiTransaction::transfer(vec![0x74, 0x00], vec![0x74, 0x0F]),
+        // Write LOCK (lock enable)
+        SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Read STATUS0 (lock check)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+        // Write LOCK (lock disable)
+        SpiTransaction::transfer(vec![0x50, 0x9C], vec![0x50, 0x9C]),
+        // Write LOCK (lock enable)
+        SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Read STATUS0 (lock check, not protected)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x00]),
+    ];
+    let cs_expectations: Vec<_> = (0..spi_expectations.len())
+        .flat_map(|_| {
+            [
+                PinTransaction::set(PinState::Low),
+                PinTransaction::set(PinState::High),
+            ]
+        })
+        .collect();
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut adc = poly::Ade791x::new(spi, [(cs, Chip::ADE7912)]);
+    assert_eq!(
+        adc.configure(|session| {
+            session.write_emi_ctrl([EmiCtrl::from(0x0F)])?;
+            session.write_emi_ctrl([EmiCtrl::from(0x00)])
+        }),
+        Err(Error::EmiCtrlEmpty { device: 0 })
+    );
+    assert_eq!(
+        adc.configure(|_| Ok(())),
+        Err(Error::LockFailed { device: 0 })
+    );
+}
+
+#[test]
+fn configure_unprotected() {
+    let spi_expectations = [
+        // Write LOCK (lock disable)
+        SpiTransaction::transfer(vec![0x50, 0x9C], vec![0x50, 0x9C]),
+        // Write CONFIG (powerdown enable)
+        SpiTransaction::transfer(vec![0x40, 0x04], vec![0x40, 0x04]),
+    ];
+    let cs_expectations = [
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ];
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut adc = poly::Ade791x::new(spi, [(cs, Chip::ADE7912)]);
+    adc.set_protection(false);
+    adc.powerdown().unwrap();
+    assert!(!adc.expected_registers()[0].locked);
+}
+
+#[test]
+fn powerdown_device() {
+    let burst_read = || {
+        // Burst Read (from IWV to V2WV)
+        SpiTransaction::transfer(
+            vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            vec![0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97],
+        )
+    };
+    let lock_check = || {
+        // Read STATUS0 (lock check)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04])
+    };
+    let dump = |config| {
+        [
+            // Burst Read (from IWV to CNT_SNAPSHOT)
+            SpiTransaction::transfer(
+                vec![
+                    0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    0x00, 0x00,
+                ],
+                vec![
+                    0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97, 0xF1, 0x24, 0x04,
+                    0x01, 0x71,
+                ],
+            ),
+            // Read CTRL_CRC
+            SpiTransaction::transfer(vec![0x2C, 0x00, 0x00], vec![0x2C, 0x8D, 0x3A]),
+            // Read CONFIG
+            SpiTransaction::transfer(vec![0x44, 0x00], vec![0x44, config]),
+            // Read STATUS1
+            SpiTransaction::transfer(vec![0x7C, 0x00], vec![0x7C, 0x01]),
+            // Read EMI_CTRL
+            SpiTransaction::transfer(vec![0x74, 0x00], vec![0x74, 0xFF]),
+            // Read TEMPOS
+            SpiTransaction::transfer(vec![0xC4, 0x00], vec![0xC4, 0x00]),
+        ]
+    };
+    let spi_expectations = [
+        vec![
+            // Write LOCK (lock disable)
+            SpiTransaction::transfer(vec![0x50, 0x9C], vec![0x50, 0x9C]),
+            // Write LOCK (lock enable)
+            SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+            lock_check(),
+            lock_check(),
+            lock_check(),
+            // Write LOCK (lock disable)
+            SpiTransaction::transfer(vec![0x50, 0x9C], vec![0x50, 0x9C]),
+            // Write LOCK (lock enable)
+            SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+            lock_check(),
+            lock_check(),
+            lock_check(),
+            // Write LOCK (lock disable)
+            SpiTransaction::transfer(vec![0x50, 0x9C], vec![0x50, 0x9C]),
+            // Write CONFIG (powerdown enable)
+            SpiTransaction::transfer(vec![0x40, 0x04], vec![0x40, 0x04]),
+            // Write LOCK (lock enable)
+            SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+            lock_check(),
+            lock_check(),
+            lock_check(),
+        ],
+        dump(0x00).to_vec(),
+        dump(0x04).to_vec(),
+        dump(0x00).to_vec(),
+        vec![
+            burst_read(),
+            burst_read(),
+            // Write LOCK (lock disable)
+            SpiTransaction::transfer(vec![0x50, 0x9C], vec![0x50, 0x9C]),
+            // Write CONFIG (powerdown disable)
+            SpiTransaction::transfer(vec![0x40, 0x00], vec![0x40, 0x00]),
+            // Write SYNC_SNAP (snap trigger)
+            SpiTransaction::transfer(vec![0x58, 0x02], vec![0x58, 0x02]),
+            // Burst Read (CNT_SNAPSHOT only)
+            SpiTransaction::transfer(vec![0x3C, 0x00, 0x00], vec![0x3C, 0x01, 0x71]),
+            SpiTransaction::transfer(vec![0x3C, 0x00, 0x00], vec![0x3C, 0x01, 0x72]),
+            // Write LOCK (lock enable)
+            SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+            lock_check(),
+            lock_check(),
+            lock_check(),
+        ],
+    ]
+    .concat();
+    let cs_expectations = |n| -> Vec<_> {
+        (0..n)
+            .flat_map(|_| {
+                [
+                    PinTransaction::set(PinState::Low),
+                    PinTransaction::set(PinState::High),
+                ]
+            })
+            .collect()
+    };
+    let spi = SpiMock::new(&spi_expectations);
+    let cs0 = PinMock::new(&cs_expectations(20));
+    let cs1 = PinMock::new(&cs_expectations(22));
+    let cs2 = PinMock::new(&cs_expectations(21));
+    let mut adc = poly::Ade791x::new(
+        spi,
+        [
+            (cs0, Chip::ADE7912),
+            (cs1, Chip::ADE7912),
+            (cs2, Chip::ADE7912),
+        ],
+    );
+    adc.set_topology(poly::Topology {
+        clock: [
+            poly::ClockSource::Crystal,
+            poly::ClockSource::Clkout(0),
+            poly::ClockSource::Clkout(0),
+        ],
+        dready: 2,
+    })
+    .unwrap();
+    assert_eq!(
+        adc.powerdown_device(2),
+        Err(Error::PowerdownNotPermitted { device: 2 })
+    );
+    assert_eq!(
+        adc.powerdown_device(0),
+        Err(Error::PowerdownNotPermitted { device: 0 })
+    );
+    adc.powerdown_device(1).unwrap();
+    assert_eq!(adc.powered_down(), [false, true, false]);
+    assert!(adc
+        .diff_registers()
+        .unwrap()
+        .iter()
+        .all(|diff| diff.is_empty()));
+    assert_eq!(
+        adc.get_raw_measurement().unwrap()[1],
+        RawMeasurement {
+            iwv: 0,
+            v1wv: 0,
+            v2wv: 0
+        }
+    );
+    assert_eq!(adc.wakeup_device(1).unwrap(), 1);
+    assert_eq!(adc.powered_down(), [false; 3]);
+}
+
+#[test]
+fn powerdown_device_chain() {
+    let session = || {
+        [
+            // Write LOCK (lock disable)
+            SpiTransaction::transfer(vec![0x50, 0x9C], vec![0x50, 0x9C]),
+            // Write LOCK (lock enable)
+            SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+            // Read STATUS0 (lock check)
+            SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+            SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+            SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+        ]
+    };
+    let init = |config, emi_ctrl| {
+        [
+            // Read STATUS0 (wait reset)
+            SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x00]),
+            // Write/Read CONFIG (checked write)
+            SpiTransaction::transfer(vec![0x40, config], vec![0x40, config]),
+            SpiTransaction::transfer(vec![0x44, 0x00], vec![0x44, config]),
+            // Write/Read EMI_CTRL (checked write)
+            SpiTransaction::transfer(vec![0x70, emi_ctrl], vec![0x70, emi_ctrl]),
+            SpiTransaction::transfer(vec![0x74, 0x00], vec![0x74, emi_ctrl]),
+            // Read TEMPOS (temperature offset)
+            SpiTransaction::transfer(vec![0xC4, 0x00], vec![0xC4, 0x00]),
+        ]
+    };
+    let spi_expectations = [
+        init(0x01, 0x55).to_vec(),
+        init(0x01, 0xAA).to_vec(),
+        init(0x00, 0x55).to_vec(),
+        vec![
+            // Write SYNC_SNAP (sync trigger)
+            SpiTransaction::transfer(vec![0x58, 0x01], vec![0x58, 0x01]),
+            // Write LOCK (lock enable)
+            SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+            // Read STATUS0 (lock check)
+            SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+            SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+            SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+        ],
+        session().to_vec(),
+        session().to_vec(),
+        session().to_vec(),
+    ]
+    .concat();
+    let cs_expectations = |n| -> Vec<_> {
+        (0..n)
+            .flat_map(|_| {
+                [
+                    PinTransaction::set(PinState::Low),
+                    PinTransaction::set(PinState::High),
+                ]
+            })
+            .collect()
+    };
+    let spi = SpiMock::new(&spi_expectations);
+    let cs0 = PinMock::new(&cs_expectations(18));
+    let cs1 = PinMock::new(&cs_expectations(18));
+    let cs2 = PinMock::new(&cs_expectations(18));
+    let mut delay = MockNoop::new();
+    let config = [
+        Config {
+            clkout_en: true,
+            ..Default::default()
+        },
+        Config {
+            clkout_en: true,
+            ..Default::default()
+        },
+        Config::default(),
+    ];
+    let calibration = [Calibration::default(); 3];
+    let emi_ctrl = [
+        EmiCtrl::from(0x55),
+        EmiCtrl::from(0xAA),
+        EmiCtrl::from(0x55),
+    ];
+    let mut adc = poly::Ade791x::new(
+        spi,
+        [
+            (cs0, Chip::ADE7912),
+            (cs1, Chip::ADE7912),
+            (cs2, Chip::ADE7912),
+        ],
+    );
+    adc.init(&mut delay, config, calibration, emi_ctrl).unwrap();
+    assert_eq!(adc.topology(), poly::Topology::chain());
+    for i in 0..3 {
+        assert_eq!(
+            adc.powerdown_device(i),
+            Err(Error::PowerdownNotPermitted { device: i })
+        );
+    }
+    assert_eq!(adc.powered_down(), [false; 3]);
+}
+
 #[test]
 fn get_raw_measurement() {
     let spi_expectations = [
@@ -362,6 +971,174 @@ fn get_raw_measurement() {
     );
 }
 
+#[test]
+fn get_raw_measurement_channels() {
+    let spi_expectations = [
+        // Burst Read (IWV only)
+        SpiTransaction::transfer(vec![0x04, 0x00, 0x00, 0x00], vec![0x04, 0x05, 0xEC, 0xDF]),
+        // Burst Read (from IWV to V1WV)
+        SpiTransaction::transfer(
+            vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            vec![0x04, 0x06, 0x13, 0x83, 0x05, 0xEC, 0x10],
+        ),
+    ];
+    let cs_expectations = [
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ];
+    let spi = SpiMock::new(&spi_expectations);
+    let cs0 = PinMock::new(&cs_expectations);
+    let cs1 = PinMock::new(&cs_expectations);
+    let mut adc = poly::Ade791x::new(spi, [(cs0, Chip::ADE7912), (cs1, Chip::ADE7913)]);
+    adc.set_channels([Channels::Iwv, Channels::IwvV1wv])
+        .unwrap();
+    assert_eq!(
+        adc.get_raw_measurement().unwrap(),
+        [
+            RawMeasurement {
+                iwv: 388319,
+                v1wv: 0,
+                v2wv: 0
+            },
+            RawMeasurement {
+                iwv: 398211,
+                v1wv: 388112,
+                v2wv: 0
+            }
+        ]
+    );
+}
+
+#[test]
+fn get_measurement_channels() {
+    let spi_expectations = [
+        // Burst Read (IWV only)
+        SpiTransaction::transfer(vec![0x04, 0x00, 0x00, 0x00], vec![0x04, 0x05, 0xEC, 0xDF]),
+        // Burst Read (from IWV to V1WV)
+        SpiTransaction::transfer(
+            vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            vec![0x04, 0x06, 0x13, 0x83, 0x05, 0xEC, 0x10],
+        ),
+    ];
+    let cs_expectations = [
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ];
+    let spi = SpiMock::new(&spi_expectations);
+    let cs0 = PinMock::new(&cs_expectations);
+    let cs1 = PinMock::new(&cs_expectations);
+    let mut adc = poly::Ade791x::new(spi, [(cs0, Chip::ADE7912), (cs1, Chip::ADE7913)]);
+    adc.set_channels([Channels::Iwv, Channels::IwvV1wv])
+        .unwrap();
+    let measurement = adc.get_measurement().unwrap();
+    assert!(measurement[0].current.is_finite());
+    assert!(measurement[0].voltage.is_nan());
+    assert_eq!(measurement[0].aux, MeasurementAux::Unavailable);
+    assert!(measurement[1].current.is_finite());
+    assert!(measurement[1].voltage.is_finite());
+    assert_eq!(measurement[1].aux, MeasurementAux::Unavailable);
+}
+
+#[test]
+fn get_sample() {
+    let spi_expectations = [
+        // Burst Read (from IWV to CNT_SNAPSHOT)
+        SpiTransaction::transfer(
+            vec![
+                0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00,
+            ],
+            vec![
+                0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97, 0xF1, 0x24, 0x04, 0x01,
+                0x71,
+            ],
+        ),
+        SpiTransaction::transfer(
+            vec![
+                0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00,
+            ],
+            vec![
+                0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97, 0x00, 0x00, 0x05, 0x01,
+                0x72,
+            ],
+        ),
+    ];
+    let cs_expectations = [
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ];
+    let spi = SpiMock::new(&spi_expectations);
+    let cs0 = PinMock::new(&cs_expectations);
+    let cs1 = PinMock::new(&cs_expectations);
+    let mut adc = poly::Ade791x::new(spi, [(cs0, Chip::ADE7912), (cs1, Chip::ADE7913)]);
+    let sample = adc.get_sample().unwrap();
+    assert!(sample[0].is_crc_valid());
+    assert!(!sample[0].is_reset());
+    assert_eq!(sample[0].cnt_snapshot, 0x0171);
+    assert!(!sample[1].is_crc_valid());
+    assert!(sample[1].is_reset());
+    assert_eq!(sample[1].cnt_snapshot, 0x0172);
+}
+
+#[test]
+fn diff_registers() {
+    let mut spi_expectations = Vec::new();
+    for status0 in [0x04, 0x00] {
+        spi_expectations.extend([
+            // Burst Read (from IWV to CNT_SNAPSHOT)
+            SpiTransaction::transfer(
+                vec![
+                    0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    0x00, 0x00,
+                ],
+                vec![
+                    0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97, 0xF1, 0x24,
+                    status0, 0x01, 0x71,
+                ],
+            ),
+            // Read CTRL_CRC
+            SpiTransaction::transfer(vec![0x2C, 0x00, 0x00], vec![0x2C, 0x8D, 0x3A]),
+            // Read CONFIG
+            SpiTransaction::transfer(vec![0x44, 0x00], vec![0x44, 0x00]),
+            // Read STATUS1
+            SpiTransaction::transfer(vec![0x7C, 0x00], vec![0x7C, 0x01]),
+            // Read EMI_CTRL
+            SpiTransaction::transfer(vec![0x74, 0x00], vec![0x74, 0xFF]),
+            // Read TEMPOS
+            SpiTransaction::transfer(vec![0xC4, 0x00], vec![0xC4, 0x00]),
+        ]);
+    }
+    let cs_expectations: Vec<_> = (0..6)
+        .flat_map(|_| {
+            [
+                PinTransaction::set(PinState::Low),
+                PinTransaction::set(PinState::High),
+            ]
+        })
+        .collect();
+    let spi = SpiMock::new(&spi_expectations);
+    let cs0 = PinMock::new(&cs_expectations);
+    let cs1 = PinMock::new(&cs_expectations);
+    let mut adc = poly::Ade791x::new(spi, [(cs0, Chip::ADE7912), (cs1, Chip::ADE7913)]);
+    let diff = adc.diff_registers().unwrap();
+    assert!(diff[0].is_empty());
+    assert_eq!(
+        diff[1],
+        dump::RegisterDiff {
+            status0: Some(dump::Mismatch {
+                expected: Status0 {
+                    reset_on: false,
+                    crc_stat: false,
+                    ic_prot: true
+                },
+                actual: Status0::default()
+            }),
+            ..Default::default()
+        }
+    );
+}
+
 #[test]
 fn get_measurement() {
     let spi_expectations = [
@@ -399,20 +1176,140 @@ fn get_measurement() {
         adc.get_measurement().unwrap(),
         [
             Measurement {
-                current: 2.2807732,
-                voltage: 37.493286,
-                aux: MeasurementAux::Temperature(3652964.53)
+                current: 2.280772,
+                voltage: 37.49328,
+                aux: MeasurementAux::Temperature(12.132141)
             },
             Measurement {
-                current: 2.3388748,
-                voltage: 36.45813,
-                aux: MeasurementAux::Voltage(342.33167)
+                current: 2.3388722,
+                voltage: 36.458096,
+                aux: MeasurementAux::Voltage(342.33157)
             },
             Measurement {
-                current: 2.2754288,
-                voltage: 37.576965,
-                aux: MeasurementAux::Temperature(3762769.53)
+                current: 2.2754273,
+                voltage: 37.576977,
+                aux: MeasurementAux::Temperature(21.708221)
             }
         ]
     );
 }
+
+#[test]
+fn split() {
+    let burst_read = || {
+        // Burst Read (from IWV to V2WV)
+        SpiTransaction::transfer(
+            vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            vec![0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97],
+        )
+    };
+    let spi_expectations = [
+        burst_read(),
+        burst_read(),
+        burst_read(),
+        burst_read(),
+        // Write LOCK (lock disable)
+        SpiTransaction::transfer(vec![0x50, 0x9C], vec![0x50, 0x9C]),
+        // Write CONFIG (powerdown enable)
+        SpiTransaction::transfer(vec![0x40, 0x04], vec![0x40, 0x04]),
+        // Write LOCK (lock enable)
+        SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Read STATUS0 (lock check)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+    ];
+    let cs_expectations: Vec<_> = (0..spi_expectations.len())
+        .flat_map(|_| {
+            [
+                PinTransaction::set(PinState::Low),
+                PinTransaction::set(PinState::High),
+            ]
+        })
+        .collect();
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let adc = poly::Ade791x::new(spi, [(cs, Chip::ADE7912)]);
+    let shared = split::Shared::<1, 2>::new();
+    let (mut reader, mut controller) = adc.split(&shared);
+    reader.read().unwrap();
+    reader.read().unwrap();
+    reader.read().unwrap();
+    assert_eq!(controller.len(), 2);
+    assert_eq!(controller.overruns(), 1);
+    assert_eq!(
+        controller.pop(),
+        Some([RawMeasurement {
+            iwv: 388319,
+            v1wv: 399132,
+            v2wv: 3653271
+        }])
+    );
+    assert_eq!(
+        controller.pop_measurement(),
+        Some([Measurement {
+            current: 2.280772,
+            voltage: 37.49328,
+            aux: MeasurementAux::Temperature(12.132141)
+        }])
+    );
+    assert_eq!(controller.pop(), None);
+    controller.post(split::Command::Powerdown).unwrap();
+    assert_eq!(
+        controller.post(split::Command::Wakeup),
+        Err(split::Command::Wakeup)
+    );
+    assert_eq!(controller.response(), None);
+    reader.read().unwrap();
+    assert_eq!(controller.response(), Some(split::Response::Done));
+    assert_eq!(controller.len(), 1);
+    assert_eq!(controller.errors(), 0);
+}
+
+#[test]
+fn split_wrap() {
+    let spi_expectations: Vec<_> = (0..20)
+        .map(|k| {
+            // Burst Read (from IWV to V2WV)
+            SpiTransaction::transfer(
+                vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+                vec![0x04, 0x00, 0x00, k, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            )
+        })
+        .collect();
+    let cs_expectations: Vec<_> = (0..spi_expectations.len())
+        .flat_map(|_| {
+            [
+                PinTransaction::set(PinState::Low),
+                PinTransaction::set(PinState::High),
+            ]
+        })
+        .collect();
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let adc = poly::Ade791x::new(spi, [(cs, Chip::ADE7912)]);
+    let shared = split::Shared::<1, 3>::new();
+    let (mut reader, mut controller) = adc.split(&shared);
+    let mut expected = 0;
+    for k in 0..10 {
+        reader.read().unwrap();
+        reader.read().unwrap();
+        assert_eq!(controller.len(), 2);
+        for _ in 0..2 {
+            assert_eq!(controller.pop().map(|raw| raw[0].iwv), Some(expected));
+            expected += 1;
+        }
+        assert!(controller.is_empty(), "iteration {k}");
+    }
+    assert_eq!(controller.overruns(), 0);
+}
+
+#[test]
+#[should_panic(expected = "already used")]
+fn split_twice() {
+    let shared = split::Shared::<1, 2>::new();
+    let spi = SpiMock::new(&[]);
+    let cs = PinMock::new(&[]);
+    let adc = poly::Ade791x::new(spi, [(cs, Chip::ADE7912)]);
+    let (reader, _) = adc.split(&shared);
+    let adc = reader.into_inner();
+    adc.split(&shared);
+}
@@ -0,0 +1,186 @@
+use ade791x::profile::*;
+use ade791x::*;
+
+fn profile() -> Profile<2> {
+    Profile {
+        chip: [Chip::ADE7913, Chip::ADE7912],
+        config: [
+            Config {
+                clkout_en: true,
+                ..Default::default()
+            },
+            Config::default(),
+        ],
+        emi_ctrl: EmiCtrl::allocate([Chip::ADE7913, Chip::ADE7912]),
+        scaling: [
+            Scaling {
+                current: 2.0,
+                voltage: 0.5,
+                aux: 0.25,
+            },
+            Scaling::default(),
+        ],
+        calibration: [
+            Calibration {
+                offset: CalibrationOffset {
+                    current: 0.01,
+                    voltage: -0.2,
+                    aux: Some(0.1),
+                },
+                tempco: CalibrationTempCo {
+                    current: TempCo::Linear { t0: 25.0, k: 1e-3 },
+                    voltage: TempCo::None,
+                },
+                ..Default::default()
+            },
+            Calibration {
+                temperature: Some(TemperatureCalibration::from_tempos(-3, false)),
+                ..Default::default()
+            },
+        ],
+    }
+}
+
+fn crc(bytes: &[u8]) -> u16 {
+    let mut crc = 0xFFFF_u16;
+    for byte in bytes {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[test]
+fn encode_decode() {
+    let profile = profile();
+    let mut buf = [0; 256];
+    let len = profile.encode(&mut buf).unwrap();
+    assert_eq!(&buf[..8], b"ADEP\x01\x02\x5F\x00");
+    assert_eq!(len, 8 + 95 + 2);
+    let decoded = Profile::<2>::decode(&buf[..len]).unwrap();
+    assert_eq!(format!("{decoded:?}"), format!("{profile:?}"));
+    let calibration = decoded.calibration();
+    assert_eq!(calibration[0].gain.current, 2.0);
+    assert_eq!(calibration[0].gain.voltage, 0.5);
+    assert_eq!(calibration[0].gain.aux, Some(0.25));
+    assert_eq!(calibration[1].gain.current, 1.0);
+
+    assert_eq!(
+        Profile::<3>::decode(&buf[..len]).unwrap_err(),
+        ProfileError::AdcCountMismatch(2)
+    );
+    assert_eq!(
+        Profile::<2>::decode(&buf[..len - 1]).unwrap_err(),
+        ProfileError::BufferTooSmall
+    );
+    assert_eq!(
+        Profile::<2>::decode(&[0xFF; 16]).unwrap_err(),
+        ProfileError::InvalidMagic
+    );
+    buf[20] ^= 0x01;
+    assert_eq!(
+        Profile::<2>::decode(&buf[..len]).unwrap_err(),
+        ProfileError::CrcMismatch
+    );
+}
+
+#[test]
+fn migrate() {
+    let profile = profile();
+    let mut buf = [0; 256];
+    let len = profile.encode(&mut buf).unwrap();
+    // Drop the scaling of the first ADC, as written before it was part of the records
+    let record_len = buf[8] as usize;
+    let mut data = buf[..9 + record_len - 12].to_vec();
+    data.extend_from_slice(&buf[9 + record_len..len - 2]);
+    data[8] -= 12;
+    data[6] -= 12;
+    data.extend_from_slice(&crc(&data).to_le_bytes());
+    let decoded = Profile::<2>::decode(&data).unwrap();
+    assert_eq!(decoded.scaling[0], Scaling::default());
+    assert_eq!(
+        format!("{:?}", decoded.calibration),
+        format!("{:?}", profile.calibration)
+    );
+}
+
+#[cfg(feature = "embedded-storage")]
+#[test]
+fn store_load() {
+    use embedded_storage::nor_flash::{
+        ErrorType, NorFlash, NorFlashError, NorFlashErrorKind, ReadNorFlash,
+    };
+
+    #[derive(Debug, PartialEq)]
+    struct FlashError(NorFlashErrorKind);
+
+    impl NorFlashError for FlashError {
+        fn kind(&self) -> NorFlashErrorKind {
+            self.0
+        }
+    }
+
+    struct Flash([u8; 512]);
+
+    impl ErrorType for Flash {
+        type Error = FlashError;
+    }
+
+    impl ReadNorFlash for Flash {
+        const READ_SIZE: usize = 4;
+
+        fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+            embedded_storage::nor_flash::check_read(self, offset, bytes.len())
+                .map_err(FlashError)?;
+            bytes.copy_from_slice(&self.0[offset as usize..offset as usize + bytes.len()]);
+            Ok(())
+        }
+
+        fn capacity(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    impl NorFlash for Flash {
+        const WRITE_SIZE: usize = 4;
+        const ERASE_SIZE: usize = 128;
+
+        fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+            embedded_storage::nor_flash::check_erase(self, from, to).map_err(FlashError)?;
+            self.0[from as usize..to as usize].fill(0xFF);
+            Ok(())
+        }
+
+        fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+            embedded_storage::nor_flash::check_write(self, offset, bytes.len())
+                .map_err(FlashError)?;
+            for (cell, byte) in self.0[offset as usize..].iter_mut().zip(bytes) {
+                *cell &= byte;
+            }
+            Ok(())
+        }
+    }
+
+    let profile = profile();
+    let mut flash = Flash([0xFF; 512]);
+    let mut buf = [0; 256];
+    assert_eq!(
+        Profile::<2>::load(&mut flash, 128, &mut buf).unwrap_err(),
+        StorageError::Profile(ProfileError::InvalidMagic)
+    );
+    let len = profile.store(&mut flash, 128, &mut buf).unwrap();
+    assert_eq!(len, 105);
+    assert!(flash.0[..128].iter().all(|b| *b == 0xFF));
+    let loaded = Profile::<2>::load(&mut flash, 128, &mut buf).unwrap();
+    assert_eq!(format!("{loaded:?}"), format!("{profile:?}"));
+    assert_eq!(
+        profile.store(&mut flash, 100, &mut buf).unwrap_err(),
+        StorageError::Storage(FlashError(NorFlashErrorKind::NotAligned))
+    );
+}
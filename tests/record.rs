@@ -0,0 +1,184 @@
+use ade791x::record::*;
+use ade791x::*;
+use embedded_hal_mock::pin::{Mock as PinMock, State as PinState, Transaction as PinTransaction};
+use embedded_hal_mock::spi::{Mock as SpiMock, Transaction as SpiTransaction};
+
+#[test]
+fn replay() {
+    let spi_expectations = [
+        // Burst Read (from IWV to V2WV)
+        SpiTransaction::transfer(
+            vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            vec![0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97],
+        ),
+        // Burst Read (from IWV to V2WV)
+        SpiTransaction::transfer(
+            vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            vec![0x04, 0xFA, 0x13, 0x21, 0x06, 0x17, 0x20, 0x37, 0xBE, 0x90],
+        ),
+    ];
+    let cs_expectations = [
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ];
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut adc = Ade791x::new_ade7912(spi, cs);
+    adc.set_calibration(Calibration {
+        tempco: CalibrationTempCo {
+            current: TempCo::Linear { t0: 25.0, k: 1e-3 },
+            voltage: TempCo::None,
+        },
+        ..Default::default()
+    });
+    let measurement = [
+        adc.get_measurement().unwrap(),
+        adc.get_measurement().unwrap(),
+    ];
+    let raw = [
+        RawMeasurement {
+            iwv: 388319,
+            v1wv: 399132,
+            v2wv: 3653271,
+        },
+        RawMeasurement {
+            iwv: -388319,
+            v1wv: 399136,
+            v2wv: 3653264,
+        },
+    ];
+    let mut buf = [0; 256];
+    let (mut recorder, mut len) =
+        Recorder::new(&adc.recording_header(true, true), &mut buf).unwrap();
+    for (k, raw) in raw.iter().enumerate() {
+        let sample = Sample {
+            cnt_snapshot: k as u16,
+            ..Sample::from(*raw)
+        };
+        len += recorder.write_frame(&[sample], &mut buf[len..]).unwrap();
+    }
+    // Header (7 bytes + 3 bytes of registers + 1 byte of channels + 38 bytes of calibration),
+    // first frame (10 bytes of varints + 5 bytes of status) and second frame (5 bytes of varints +
+    // 5 bytes of status)
+    assert_eq!(len, 74);
+    let mut replay = Replay::<1>::new(&buf[..len]).unwrap();
+    assert_eq!(replay.header().chip, [Chip::ADE7912]);
+    assert_eq!(replay.header().channels, [Channels::All]);
+    assert_eq!(replay.next_sample(), Some(Ok([Sample::from(raw[0])])));
+    assert_eq!(
+        replay.next_sample(),
+        Some(Ok([Sample {
+            cnt_snapshot: 1,
+            ..Sample::from(raw[1])
+        }]))
+    );
+    assert_eq!(replay.next_sample(), None);
+    let mut replay = Replay::<1>::new(&buf[..len]).unwrap();
+    assert_eq!(replay.next_measurement(), Some(Ok([measurement[0]])));
+    assert_eq!(replay.next_measurement(), Some(Ok([measurement[1]])));
+    assert_eq!(replay.next_measurement(), None);
+    let mut replay = Replay::<1>::new(&buf[..len - 1]).unwrap();
+    replay.next_sample();
+    assert_eq!(replay.next_sample(), Some(Err(RecordError::BufferTooSmall)));
+}
+
+#[test]
+fn replay_corrupted() {
+    let header = Header {
+        chip: [Chip::ADE7912],
+        config: [Config::default()],
+        emi_ctrl: [EmiCtrl::default()],
+        channels: [Channels::All],
+        calibration: [Calibration::default()],
+        status: false,
+        delta: true,
+    };
+    let raw = RawMeasurement {
+        iwv: 388319,
+        v1wv: 399132,
+        v2wv: 3653271,
+    };
+    let mut buf = [0; 128];
+    let (mut recorder, mut len) = Recorder::new(&header, &mut buf).unwrap();
+    len += recorder
+        .write_frame(&[Sample::from(raw)], &mut buf[len..])
+        .unwrap();
+    // Corrupted frame (varint longer than 5 bytes)
+    buf[len..len + 6].fill(0xFF);
+    len += 6;
+    for _ in 0..2 {
+        len += recorder
+            .write_frame(&[Sample::from(raw)], &mut buf[len..])
+            .unwrap();
+    }
+    let mut replay = Replay::<1>::new(&buf[..len]).unwrap();
+    assert_eq!(replay.next_sample(), Some(Ok([Sample::from(raw)])));
+    assert_eq!(replay.next_sample(), Some(Err(RecordError::InvalidData)));
+    assert_eq!(replay.next_sample(), None);
+    assert_eq!(replay.next_measurement(), None);
+}
+
+#[test]
+fn replay_invalid() {
+    let header = Header {
+        chip: [Chip::ADE7913; 2],
+        config: [Config::default(); 2],
+        emi_ctrl: EmiCtrl::allocate([Chip::ADE7913; 2]),
+        channels: [Channels::All, Channels::Iwv],
+        calibration: [Calibration::default(); 2],
+        status: false,
+        delta: false,
+    };
+    let mut buf = [0; 128];
+    let (_, len) = Recorder::new(&header, &mut buf).unwrap();
+    assert_eq!(
+        Recorder::new(&header, &mut buf[..len - 1]).err(),
+        Some(RecordError::BufferTooSmall)
+    );
+    assert_eq!(
+        Replay::<3>::new(&buf[..len]).err(),
+        Some(RecordError::AdcCountMismatch(2))
+    );
+    assert_eq!(
+        Replay::<2>::new(&buf[..len]).unwrap().header().channels,
+        [Channels::All, Channels::Iwv]
+    );
+    buf[10] = 4;
+    assert_eq!(
+        Replay::<2>::new(&buf[..len]).err(),
+        Some(RecordError::InvalidData)
+    );
+    buf[4] = 3;
+    assert_eq!(
+        Replay::<2>::new(&buf[..len]).err(),
+        Some(RecordError::UnsupportedVersion(3))
+    );
+    buf[0] = 0;
+    assert_eq!(
+        Replay::<2>::new(&buf[..len]).err(),
+        Some(RecordError::InvalidMagic)
+    );
+}
+
+#[test]
+fn replay_version_1() {
+    let header = Header {
+        chip: [Chip::ADE7912],
+        config: [Config::default()],
+        emi_ctrl: [EmiCtrl::default()],
+        channels: [Channels::Iwv],
+        calibration: [Calibration::default()],
+        status: false,
+        delta: false,
+    };
+    let mut buf = [0; 64];
+    let (_, len) = Recorder::new(&header, &mut buf).unwrap();
+    // Version 1 header, without the channels after EMI_CTRL
+    buf[4] = 1;
+    buf.copy_within(11..len, 10);
+    let replay = Replay::<1>::new(&buf[..len - 1]).unwrap();
+    assert_eq!(replay.header().channels, [Channels::All]);
+    assert_eq!(replay.header().chip, [Chip::ADE7912]);
+}
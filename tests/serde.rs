@@ -0,0 +1,69 @@
+#![cfg(feature = "serde")]
+
+use ade791x::*;
+
+#[test]
+fn config() {
+    let config = Config {
+        clkout_en: true,
+        adc_freq: AdcFreqVal::KHz4,
+        swrst: true,
+        ..Default::default()
+    };
+    let json = serde_json::to_string(&config).unwrap();
+    assert_eq!(
+        json,
+        r#"{"clkout_en":true,"pwrdwn_en":false,"temp_en":false,"adc_freq":"4kHz","bw":false}"#
+    );
+    let decoded: Config = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        decoded,
+        Config {
+            swrst: false,
+            ..config
+        }
+    );
+    let decoded: Config = serde_json::from_str(r#"{"temp_en":true}"#).unwrap();
+    assert_eq!(
+        decoded,
+        Config {
+            temp_en: true,
+            ..Default::default()
+        }
+    );
+    let emi_ctrl: EmiCtrl = serde_json::from_str("73").unwrap();
+    assert_eq!(u8::from(emi_ctrl), 0x49);
+    assert_eq!(serde_json::to_string(&emi_ctrl).unwrap(), "73");
+}
+
+#[test]
+fn calibration() {
+    let calibration = Calibration {
+        offset: CalibrationOffset {
+            current: 0.5,
+            voltage: -1.0,
+            aux: None,
+        },
+        gain: CalibrationGain {
+            current: 1.25,
+            voltage: 0.75,
+            aux: Some(2.0),
+        },
+        temperature: None,
+        tempco: CalibrationTempCo {
+            current: TempCo::Linear { t0: 25.0, k: 0.5 },
+            voltage: TempCo::None,
+        },
+    };
+    let json = serde_json::to_string(&calibration).unwrap();
+    assert_eq!(
+        json,
+        r#"{"offset":{"current":0.5,"voltage":-1.0,"aux":null},"gain":{"current":1.25,"voltage":0.75,"aux":2.0},"temperature":null,"tempco":{"current":{"Linear":{"t0":25.0,"k":0.5}},"voltage":"None"}}"#
+    );
+    let decoded: Calibration = serde_json::from_str(&json).unwrap();
+    assert_eq!(serde_json::to_string(&decoded).unwrap(), json);
+    let decoded: Calibration = serde_json::from_str(r#"{"gain":{"current":2.0}}"#).unwrap();
+    assert_eq!(decoded.gain.current, 2.0);
+    assert_eq!(decoded.gain.voltage, 1.0);
+    assert_eq!(decoded.offset.current, 0.0);
+}
@@ -2,6 +2,8 @@ use ade791x::*;
 use embedded_hal_mock::delay::MockNoop;
 use embedded_hal_mock::pin::{Mock as PinMock, State as PinState, Transaction as PinTransaction};
 use embedded_hal_mock::spi::{Mock as SpiMock, Transaction as SpiTransaction};
+use embedded_hal_mock::MockError;
+use std::io::ErrorKind;
 
 #[test]
 fn init_ade7912() {
@@ -18,6 +20,8 @@ fn init_ade7912() {
         SpiTransaction::transfer(vec![0xC4, 0x00], vec![0xC4, 0x5E]),
         // Write LOCK (lock enable)
         SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Read STATUS0 (lock check)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
     ];
     let cs_expectations = [
         PinTransaction::set(PinState::Low),
@@ -34,6 +38,8 @@ fn init_ade7912() {
         PinTransaction::set(PinState::High),
         PinTransaction::set(PinState::Low),
         PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
     ];
     let spi = SpiMock::new(&spi_expectations);
     let cs = PinMock::new(&cs_expectations);
@@ -57,6 +63,8 @@ fn init_ade7913() {
         SpiTransaction::transfer(vec![0x74, 0x00], vec![0x74, 0xFF]),
         // Write LOCK (lock enable)
         SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Read STATUS0 (lock check)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
     ];
     let cs_expectations = [
         PinTransaction::set(PinState::Low),
@@ -71,6 +79,8 @@ fn init_ade7913() {
         PinTransaction::set(PinState::High),
         PinTransaction::set(PinState::Low),
         PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
     ];
     let spi = SpiMock::new(&spi_expectations);
     let cs = PinMock::new(&cs_expectations);
@@ -90,6 +100,8 @@ fn init_timeout() {
         SpiTransaction::transfer(vec![0x4c, 0x00], vec![0x4c, 0x01]),
         SpiTransaction::transfer(vec![0x4c, 0x00], vec![0x4c, 0x01]),
         SpiTransaction::transfer(vec![0x4c, 0x00], vec![0x4c, 0x01]),
+        // Write LOCK (lock enable)
+        SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
     ];
     let cs_expectations = [
         PinTransaction::set(PinState::Low),
@@ -102,6 +114,8 @@ fn init_timeout() {
         PinTransaction::set(PinState::High),
         PinTransaction::set(PinState::Low),
         PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
     ];
     let spi = SpiMock::new(&spi_expectations);
     let cs = PinMock::new(&cs_expectations);
@@ -111,7 +125,110 @@ fn init_timeout() {
     let mut adc = Ade791x::new_ade7912(spi, cs);
     assert_eq!(
         adc.init(&mut delay, config, calibration),
-        Err(Error::ResetTimeout)
+        Err(Error::ResetTimeout { device: 0 })
+    );
+}
+
+#[test]
+fn init_content_mismatch() {
+    let spi_expectations = [
+        // Read STATUS0 (wait reset)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x00]),
+        // Write/Read CONFIG (checked write, registers locked)
+        SpiTransaction::transfer(vec![0x40, 0x01], vec![0x40, 0x00]),
+        SpiTransaction::transfer(vec![0x44, 0x00], vec![0x44, 0x00]),
+        // Write LOCK (lock enable)
+        SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+    ];
+    let cs_expectations = [
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ];
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut delay = MockNoop::new();
+    let config = Config {
+        clkout_en: true,
+        ..Default::default()
+    };
+    let calibration = Calibration::default();
+    let mut adc = Ade791x::new_ade7912(spi, cs);
+    let error = adc.init(&mut delay, config, calibration).unwrap_err();
+    assert_eq!(
+        error,
+        Error::RegisterContentMismatch {
+            device: 0,
+            register: Register::Config,
+            expected: 0x01,
+            actual: 0x00
+        }
+    );
+    assert_eq!(error.recovery(), Recovery::Reinit);
+    assert!(!error.is_transient());
+}
+
+#[test]
+fn init_retry() {
+    let spi_expectations = [
+        // Read STATUS0 (wait reset)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x00]),
+        // Write/Read CONFIG (checked write, glitch)
+        SpiTransaction::transfer(vec![0x40, 0x01], vec![0x40, 0x01]),
+        SpiTransaction::transfer(vec![0x44, 0x00], vec![0x44, 0x03]),
+        // Write/Read CONFIG (checked write, retry)
+        SpiTransaction::transfer(vec![0x40, 0x01], vec![0x40, 0x01]),
+        SpiTransaction::transfer(vec![0x44, 0x00], vec![0x44, 0x01]),
+        // Write/Read EMI_CTRL (checked write)
+        SpiTransaction::transfer(vec![0x70, 0xFF], vec![0x70, 0xFF]),
+        SpiTransaction::transfer(vec![0x74, 0x00], vec![0x74, 0xFF]),
+        // Read TEMPOS (temperature offset)
+        SpiTransaction::transfer(vec![0xC4, 0x00], vec![0xC4, 0x5E]),
+        // Write LOCK (lock enable)
+        SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Read STATUS0 (lock check)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+    ];
+    let mut cs_expectations = vec![
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::High),
+    ];
+    for _ in 0..7 {
+        cs_expectations.push(PinTransaction::set(PinState::Low));
+        cs_expectations.push(PinTransaction::set(PinState::High));
+    }
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut delay = MockNoop::new();
+    let config = Config {
+        clkout_en: true,
+        ..Default::default()
+    };
+    let calibration = Calibration::default();
+    let mut adc = Ade791x::new_ade7912(spi, cs);
+    adc.set_retry_policy(RetryPolicy {
+        max_attempts: 3,
+        backoff_ms: 10,
+        ..Default::default()
+    });
+    adc.init(&mut delay, config, calibration).unwrap();
+    assert_eq!(
+        adc.retry_counters(),
+        RetryCounters {
+            retries: 1,
+            recovered: 1,
+            exhausted: 0
+        }
     );
 }
 
@@ -160,6 +277,8 @@ fn powerdown() {
         SpiTransaction::transfer(vec![0x40, 0x04], vec![0x40, 0x04]),
         // Write LOCK (lock enable)
         SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Read STATUS0 (lock check)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
     ];
     let cs_expectations = [
         PinTransaction::set(PinState::Low),
@@ -168,6 +287,8 @@ fn powerdown() {
         PinTransaction::set(PinState::High),
         PinTransaction::set(PinState::Low),
         PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
     ];
     let spi = SpiMock::new(&spi_expectations);
     let cs = PinMock::new(&cs_expectations);
@@ -175,6 +296,49 @@ fn powerdown() {
     adc.powerdown().unwrap();
 }
 
+#[test]
+fn powerdown_retry() {
+    let spi_expectations = [
+        // Write LOCK (lock disable)
+        SpiTransaction::transfer(vec![0x50, 0x9C], vec![0x50, 0x9C]),
+        // Write CONFIG (powerdown enable, retry)
+        SpiTransaction::transfer(vec![0x40, 0x04], vec![0x40, 0x04]),
+        // Write LOCK (lock enable)
+        SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Read STATUS0 (lock check)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+    ];
+    let cs_expectations = [
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        // Glitch on the CONFIG write
+        PinTransaction::set(PinState::Low).with_error(MockError::Io(ErrorKind::Other)),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ];
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut adc = Ade791x::new_ade7912(spi, cs);
+    adc.set_retry_policy(RetryPolicy {
+        max_attempts: 2,
+        ..Default::default()
+    });
+    adc.powerdown().unwrap();
+    assert_eq!(
+        adc.retry_counters(),
+        RetryCounters {
+            retries: 1,
+            recovered: 1,
+            exhausted: 0
+        }
+    );
+}
+
 #[test]
 fn wakeup() {
     let spi_expectations = [
@@ -184,6 +348,8 @@ fn wakeup() {
         SpiTransaction::transfer(vec![0x40, 0x00], vec![0x40, 0x00]),
         // Write LOCK (lock disable)
         SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Read STATUS0 (lock check)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
     ];
     let cs_expectations = [
         PinTransaction::set(PinState::Low),
@@ -192,6 +358,8 @@ fn wakeup() {
         PinTransaction::set(PinState::High),
         PinTransaction::set(PinState::Low),
         PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
     ];
     let spi = SpiMock::new(&spi_expectations);
     let cs = PinMock::new(&cs_expectations);
@@ -244,9 +412,9 @@ fn get_measurement_ade7912() {
     assert_eq!(
         adc.get_measurement().unwrap(),
         Measurement {
-            current: 2.2807732,
-            voltage: 37.493286,
-            aux: MeasurementAux::Temperature(3652964.5)
+            current: 2.280772,
+            voltage: 37.49328,
+            aux: MeasurementAux::Temperature(12.132141)
         }
     );
 }
@@ -270,9 +438,885 @@ fn get_measurement_ade7913() {
     assert_eq!(
         adc.get_measurement().unwrap(),
         Measurement {
-            current: 2.2807732,
-            voltage: 37.493286,
+            current: 2.280772,
+            voltage: 37.49328,
             aux: MeasurementAux::Voltage(343.17712)
         }
     );
 }
+
+#[test]
+fn get_tracked_measurement() {
+    let spi_expectations = [
+        // Burst Read (from IWV to V2WV)
+        SpiTransaction::transfer(
+            vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            vec![0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97],
+        ),
+        // Burst Read (from IWV to V2WV)
+        SpiTransaction::transfer(
+            vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            vec![0x04, 0x05, 0xEC, 0xE0, 0x06, 0x17, 0x1D, 0x37, 0xBE, 0x98],
+        ),
+        // Burst Read (from IWV to V2WV)
+        SpiTransaction::transfer(
+            vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            vec![0x04, 0x05, 0xEC, 0xE0, 0x06, 0x17, 0x1D, 0x37, 0xBE, 0x98],
+        ),
+        // Burst Read (from IWV to V2WV)
+        SpiTransaction::transfer(
+            vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            vec![0x04, 0x05, 0xEC, 0xE1, 0x06, 0x17, 0x1E, 0x37, 0xBE, 0x99],
+        ),
+        // Burst Read (from IWV to V2WV)
+        SpiTransaction::transfer(
+            vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            vec![0x04, 0x05, 0xEC, 0xE1, 0x06, 0x17, 0x1E, 0x37, 0xBE, 0x99],
+        ),
+    ];
+    let cs_expectations: Vec<_> = (0..spi_expectations.len())
+        .flat_map(|_| {
+            [
+                PinTransaction::set(PinState::Low),
+                PinTransaction::set(PinState::High),
+            ]
+        })
+        .collect();
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut adc = Ade791x::new_ade7912(spi, cs);
+    let tracked = adc.get_tracked_measurement(1000).unwrap();
+    assert_eq!(
+        tracked.value,
+        Measurement {
+            current: 2.280772,
+            voltage: 37.49328,
+            aux: MeasurementAux::Temperature(12.132141)
+        }
+    );
+    assert_eq!(
+        (tracked.index, tracked.missed, tracked.duplicate),
+        (0, 0, false)
+    );
+    let tracked = adc.get_tracked_measurement(1130).unwrap();
+    assert_eq!(
+        (tracked.index, tracked.missed, tracked.duplicate),
+        (1, 0, false)
+    );
+    let tracked = adc.get_tracked_measurement(1140).unwrap();
+    assert_eq!(
+        (tracked.index, tracked.missed, tracked.duplicate),
+        (1, 0, true)
+    );
+    let tracked = adc.get_tracked_measurement(1510).unwrap();
+    assert_eq!(
+        (tracked.index, tracked.missed, tracked.duplicate),
+        (4, 2, false)
+    );
+    let tracked = adc.get_tracked_measurement(1635).unwrap();
+    assert_eq!(
+        (tracked.index, tracked.missed, tracked.duplicate),
+        (5, 0, false)
+    );
+    assert_eq!(adc.tracker().missed(), 2);
+    assert_eq!(adc.tracker().duplicates(), 1);
+}
+
+#[test]
+fn collect() {
+    let spi_expectations = [
+        // Burst Read (from IWV to V2WV)
+        SpiTransaction::transfer(
+            vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            vec![0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97],
+        ),
+        // Burst Read (from IWV to V2WV)
+        SpiTransaction::transfer(
+            vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            vec![0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97],
+        ),
+    ];
+    let cs_expectations = [
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ];
+    let dready_expectations = [
+        // Wait for the end of the previous DREADY pulse
+        PinTransaction::get(PinState::Low),
+        PinTransaction::get(PinState::High),
+        // Wait for the falling edge
+        PinTransaction::get(PinState::High),
+        PinTransaction::get(PinState::High),
+        PinTransaction::get(PinState::Low),
+        // Wait for the end of the previous DREADY pulse
+        PinTransaction::get(PinState::High),
+        // Wait for the falling edge
+        PinTransaction::get(PinState::Low),
+    ];
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let dready = PinMock::new(&dready_expectations);
+    let mut adc = Ade791x::new_ade7912(spi, cs).with_dready(dready);
+    let mut buffer = [Measurement {
+        current: 0.0,
+        voltage: 0.0,
+        aux: MeasurementAux::Unavailable,
+    }; 2];
+    adc.collect(&mut MockNoop::new(), &mut buffer).unwrap();
+    assert_eq!(
+        buffer,
+        [Measurement {
+            current: 2.280772,
+            voltage: 37.49328,
+            aux: MeasurementAux::Temperature(12.132141)
+        }; 2]
+    );
+}
+
+#[test]
+fn next_measurement_stuck() {
+    // Poll the DREADY line until the watchdog expires after 4 periods of 125 µs, once for the end of
+    // the previous pulse and 501 times for the falling edge
+    let dready_expectations: Vec<_> = (0..502)
+        .map(|_| PinTransaction::get(PinState::High))
+        .collect();
+    let spi = SpiMock::new(&[]);
+    let cs = PinMock::new(&[]);
+    let dready = PinMock::new(&dready_expectations);
+    let mut adc = Ade791x::new_ade7912(spi, cs).with_dready(dready);
+    assert_eq!(
+        adc.next_measurement(&mut MockNoop::new()),
+        Err(Error::DreadyStuckHigh)
+    );
+}
+
+#[test]
+fn get_sample() {
+    let spi_expectations = [
+        // Burst Read (from IWV to CNT_SNAPSHOT)
+        SpiTransaction::transfer(
+            vec![
+                0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00,
+            ],
+            vec![
+                0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97, 0xF1, 0x24, 0x04, 0x01,
+                0x71,
+            ],
+        ),
+    ];
+    let cs_expectations = [
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ];
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut adc = Ade791x::new_ade7912(spi, cs);
+    let sample = adc.get_sample().unwrap();
+    assert_eq!(
+        sample,
+        Sample {
+            raw: RawMeasurement {
+                iwv: 388319,
+                v1wv: 399132,
+                v2wv: 3653271
+            },
+            adc_crc: 0xF124,
+            status0: Status0 {
+                reset_on: false,
+                crc_stat: false,
+                ic_prot: true
+            },
+            cnt_snapshot: 0x0171
+        }
+    );
+    assert!(sample.is_crc_valid());
+    assert!(!sample.is_reset());
+}
+
+#[test]
+fn dump_registers() {
+    let spi_expectations = [
+        // Burst Read (from IWV to CNT_SNAPSHOT)
+        SpiTransaction::transfer(
+            vec![
+                0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00,
+            ],
+            vec![
+                0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97, 0xF1, 0x24, 0x04, 0x01,
+                0x71,
+            ],
+        ),
+        // Read CTRL_CRC
+        SpiTransaction::transfer(vec![0x2C, 0x00, 0x00], vec![0x2C, 0x8D, 0x3A]),
+        // Read CONFIG
+        SpiTransaction::transfer(vec![0x44, 0x00], vec![0x44, 0x01]),
+        // Read STATUS1
+        SpiTransaction::transfer(vec![0x7C, 0x00], vec![0x7C, 0x09]),
+        // Read EMI_CTRL
+        SpiTransaction::transfer(vec![0x74, 0x00], vec![0x74, 0xFF]),
+        // Read TEMPOS
+        SpiTransaction::transfer(vec![0xC4, 0x00], vec![0xC4, 0xFE]),
+    ];
+    let cs_expectations: Vec<_> = (0..6)
+        .flat_map(|_| {
+            [
+                PinTransaction::set(PinState::Low),
+                PinTransaction::set(PinState::High),
+            ]
+        })
+        .collect();
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut adc = Ade791x::new_ade7912(spi, cs);
+    let dump = adc.dump_registers().unwrap();
+    assert_eq!(
+        dump,
+        dump::RegisterDump {
+            iwv: 388319,
+            v1wv: 399132,
+            v2wv: 3653271,
+            adc_crc: 0xF124,
+            ctrl_crc: 0x8D3A,
+            cnt_snapshot: 0x0171,
+            config: Config {
+                clkout_en: true,
+                ..Default::default()
+            },
+            status0: Status0 {
+                reset_on: false,
+                crc_stat: false,
+                ic_prot: true
+            },
+            status1: Status1 {
+                version: 1,
+                adc_na: true
+            },
+            emi_ctrl: EmiCtrl::default(),
+            tempos: -2,
+        }
+    );
+    let diff = dump.diff(&adc.expected_registers());
+    assert!(!diff.is_empty());
+    assert_eq!(
+        diff.config,
+        Some(dump::Mismatch {
+            expected: Config::default(),
+            actual: dump.config
+        })
+    );
+    assert_eq!(diff.emi_ctrl, None);
+    assert_eq!(diff.status0, None);
+}
+
+#[test]
+fn get_raw_measurement_iwv() {
+    let spi_expectations = [
+        // Burst Read (IWV only)
+        SpiTransaction::transfer(vec![0x04, 0x00, 0x00, 0x00], vec![0x04, 0x05, 0xEC, 0xDF]),
+    ];
+    let cs_expectations = [
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ];
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut adc = Ade791x::new_ade7912(spi, cs);
+    adc.set_channels(Channels::Iwv).unwrap();
+    assert_eq!(
+        adc.get_raw_measurement().unwrap(),
+        RawMeasurement {
+            iwv: 388319,
+            v1wv: 0,
+            v2wv: 0
+        }
+    );
+}
+
+#[test]
+fn get_raw_measurement_crc() {
+    let spi_expectations = [
+        // Burst Read (from IWV to ADC_CRC)
+        SpiTransaction::transfer(
+            vec![
+                0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            ],
+            vec![
+                0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97, 0xF1, 0x24,
+            ],
+        ),
+        SpiTransaction::transfer(
+            vec![
+                0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            ],
+            vec![
+                0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x96, 0xF1, 0x24,
+            ],
+        ),
+    ];
+    let cs_expectations = [
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ];
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut adc = Ade791x::new_ade7912(spi, cs);
+    adc.set_channels(Channels::AllCrc).unwrap();
+    assert_eq!(
+        adc.get_raw_measurement().unwrap(),
+        RawMeasurement {
+            iwv: 388319,
+            v1wv: 399132,
+            v2wv: 3653271
+        }
+    );
+    let error = adc.get_raw_measurement().unwrap_err();
+    assert_eq!(
+        error,
+        Error::CrcMismatch {
+            device: 0,
+            expected: 0xE105,
+            actual: 0xF124
+        }
+    );
+    assert!(error.is_transient());
+    assert_eq!(error.device(), Some(0));
+}
+
+#[test]
+fn get_raw_measurement_retry() {
+    let spi_expectations = [
+        // Burst Read (from IWV to ADC_CRC, CRC mismatch)
+        SpiTransaction::transfer(
+            vec![
+                0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            ],
+            vec![
+                0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x96, 0xF1, 0x24,
+            ],
+        ),
+        // Burst Read (from IWV to ADC_CRC, retry)
+        SpiTransaction::transfer(
+            vec![
+                0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            ],
+            vec![
+                0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97, 0xF1, 0x24,
+            ],
+        ),
+        // Burst Read (from IWV to ADC_CRC, CRC mismatch)
+        SpiTransaction::transfer(
+            vec![
+                0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            ],
+            vec![
+                0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x96, 0xF1, 0x24,
+            ],
+        ),
+        // Burst Read (from IWV to ADC_CRC, retry with CRC mismatch)
+        SpiTransaction::transfer(
+            vec![
+                0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            ],
+            vec![
+                0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x96, 0xF1, 0x24,
+            ],
+        ),
+    ];
+    let cs_expectations = [
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ];
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut adc = Ade791x::new_ade7912(spi, cs);
+    adc.set_channels(Channels::AllCrc).unwrap();
+    adc.set_retry_policy(RetryPolicy {
+        max_attempts: 2,
+        ..Default::default()
+    });
+    assert_eq!(
+        adc.get_raw_measurement().unwrap(),
+        RawMeasurement {
+            iwv: 388319,
+            v1wv: 399132,
+            v2wv: 3653271
+        }
+    );
+    assert!(matches!(
+        adc.get_raw_measurement(),
+        Err(Error::CrcMismatch { .. })
+    ));
+    assert_eq!(
+        adc.retry_counters(),
+        RetryCounters {
+            retries: 2,
+            recovered: 1,
+            exhausted: 1
+        }
+    );
+    adc.reset_retry_counters();
+    assert_eq!(adc.retry_counters(), RetryCounters::default());
+}
+
+#[test]
+fn get_measurement_calibrated() {
+    let spi_expectations = [
+        // Burst Read (from IWV to V2WV)
+        SpiTransaction::transfer(
+            vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            vec![0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97],
+        ),
+    ];
+    let cs_expectations = [
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ];
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut adc = Ade791x::new_ade7913(spi, cs);
+    adc.set_calibration(Calibration {
+        offset: CalibrationOffset {
+            current: 0.280772,
+            voltage: 7.49328,
+            aux: Some(43.17709),
+        },
+        gain: CalibrationGain {
+            current: 2.0,
+            voltage: 0.5,
+            aux: Some(0.1),
+        },
+        temperature: None,
+        tempco: CalibrationTempCo::default(),
+    });
+    assert_eq!(
+        adc.conversion().current,
+        Coefficients {
+            scale: 1.1746884e-5,
+            offset: -0.5615381
+        }
+    );
+    assert_eq!(
+        adc.get_measurement().unwrap(),
+        Measurement {
+            current: 4.0,
+            voltage: 15.0,
+            aux: MeasurementAux::Voltage(30.0)
+        }
+    );
+}
+
+#[test]
+fn read_temperature() {
+    let spi_expectations = [
+        // Read STATUS0 (wait reset)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x00]),
+        // Write/Read CONFIG (checked write)
+        SpiTransaction::transfer(vec![0x40, 0x80], vec![0x40, 0x80]),
+        SpiTransaction::transfer(vec![0x44, 0x00], vec![0x44, 0x80]),
+        // Write/Read EMI_CTRL (checked write)
+        SpiTransaction::transfer(vec![0x70, 0xFF], vec![0x70, 0xFF]),
+        SpiTransaction::transfer(vec![0x74, 0x00], vec![0x74, 0xFF]),
+        // Read TEMPOS (temperature offset)
+        SpiTransaction::transfer(vec![0xC4, 0x00], vec![0xC4, 0x5E]),
+        // Write LOCK (lock enable)
+        SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Read STATUS0 (lock check)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+        // Burst Read (V2WV only)
+        SpiTransaction::transfer(vec![0x14, 0x00, 0x00, 0x00], vec![0x14, 0x37, 0xBE, 0x97]),
+        SpiTransaction::transfer(vec![0x14, 0x00, 0x00, 0x00], vec![0x14, 0x37, 0x9B, 0x6E]),
+    ];
+    let cs_expectations = [
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ];
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut delay = MockNoop::new();
+    let config = Config {
+        bw: true,
+        ..Default::default()
+    };
+    let calibration = Calibration::default();
+    let mut adc = Ade791x::new_ade7912(spi, cs);
+    adc.init(&mut delay, config, calibration).unwrap();
+    assert_eq!(adc.read_temperature(&mut delay, 2).unwrap(), 8.905029);
+}
+
+#[test]
+fn read_temperature_disabled() {
+    let spi = SpiMock::new(&[]);
+    let cs = PinMock::new(&[]);
+    let mut delay = MockNoop::new();
+    let mut adc = Ade791x::new_ade7913(spi, cs);
+    assert_eq!(
+        adc.read_temperature(&mut delay, 4),
+        Err(Error::TemperatureDisabled { device: 0 })
+    );
+}
+
+#[test]
+fn temperature_schedule() {
+    let burst_read = |v2wv: [u8; 3]| {
+        SpiTransaction::transfer(
+            vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            vec![
+                0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, v2wv[0], v2wv[1], v2wv[2],
+            ],
+        )
+    };
+    let spi_expectations = [
+        // Read TEMPOS (temperature offset)
+        SpiTransaction::transfer(vec![0xC4, 0x00], vec![0xC4, 0x5E]),
+        // Burst Read (from IWV to V2WV, voltage)
+        burst_read([0x37, 0xBE, 0x97]),
+        burst_read([0x37, 0xBE, 0x97]),
+        // Write LOCK (lock disable)
+        SpiTransaction::transfer(vec![0x50, 0x9C], vec![0x50, 0x9C]),
+        // Write/Read CONFIG (temperature enable, checked write)
+        SpiTransaction::transfer(vec![0x40, 0x08], vec![0x40, 0x08]),
+        SpiTransaction::transfer(vec![0x44, 0x00], vec![0x44, 0x08]),
+        // Write LOCK (lock enable)
+        SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Read STATUS0 (lock check)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+        // Burst Read (from IWV to V2WV, settling)
+        burst_read([0x00, 0x00, 0x00]),
+        // Burst Read (from IWV to V2WV, temperature)
+        burst_read([0x37, 0xBE, 0x97]),
+        burst_read([0x37, 0x9B, 0x6E]),
+        // Write LOCK (lock disable)
+        SpiTransaction::transfer(vec![0x50, 0x9C], vec![0x50, 0x9C]),
+        // Write/Read CONFIG (temperature disable, checked write)
+        SpiTransaction::transfer(vec![0x40, 0x00], vec![0x40, 0x00]),
+        SpiTransaction::transfer(vec![0x44, 0x00], vec![0x44, 0x00]),
+        // Write LOCK (lock enable)
+        SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Read STATUS0 (lock check)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+        // Burst Read (from IWV to V2WV, settling)
+        burst_read([0x00, 0x00, 0x00]),
+        // Burst Read (from IWV to V2WV, voltage)
+        burst_read([0x37, 0xBE, 0x97]),
+    ];
+    let cs_expectations: Vec<_> = (0..spi_expectations.len())
+        .flat_map(|_| {
+            [
+                PinTransaction::set(PinState::Low),
+                PinTransaction::set(PinState::High),
+            ]
+        })
+        .collect();
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut adc = Ade791x::new_ade7913(spi, cs);
+    adc.set_temperature_schedule(Some(TemperatureSchedule {
+        interval: 2,
+        settling: 1,
+        window: 2,
+    }))
+    .unwrap();
+    let mut aux = Vec::new();
+    let mut temp_en = Vec::new();
+    for _ in 0..7 {
+        aux.push(adc.get_measurement().unwrap().aux);
+        temp_en.push(adc.expected_registers().config.temp_en);
+    }
+    assert_eq!(temp_en, [false, true, true, true, false, false, false]);
+    assert_eq!(
+        aux,
+        [
+            MeasurementAux::Voltage(343.17712),
+            MeasurementAux::Voltage(343.17712),
+            MeasurementAux::Unavailable,
+            MeasurementAux::Unavailable,
+            MeasurementAux::Unavailable,
+            MeasurementAux::Unavailable,
+            MeasurementAux::Voltage(343.17712),
+        ]
+    );
+    assert_eq!(adc.take_temperature(), Some(28.528656));
+    assert_eq!(adc.take_temperature(), None);
+}
+
+#[test]
+fn temperature_schedule_protection() {
+    let burst_read = SpiTransaction::transfer(
+        vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        vec![0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97],
+    );
+    let spi_expectations = [
+        // Read TEMPOS (temperature offset)
+        SpiTransaction::transfer(vec![0xC4, 0x00], vec![0xC4, 0x5E]),
+        // Burst Read (from IWV to V2WV, voltage)
+        burst_read.clone(),
+        burst_read.clone(),
+        // Write/Read CONFIG (temperature enable, checked write)
+        SpiTransaction::transfer(vec![0x40, 0x08], vec![0x40, 0x08]),
+        SpiTransaction::transfer(vec![0x44, 0x00], vec![0x44, 0x08]),
+        // Read TEMPOS (temperature offset)
+        SpiTransaction::transfer(vec![0xC4, 0x00], vec![0xC4, 0x5E]),
+        // Burst Read (from IWV to V2WV, voltage)
+        burst_read.clone(),
+        burst_read,
+        // Write LOCK (lock disable)
+        SpiTransaction::transfer(vec![0x50, 0x9C], vec![0x50, 0x9C]),
+        // Write/Read CONFIG (temperature enable, checked write)
+        SpiTransaction::transfer(vec![0x40, 0x08], vec![0x40, 0x08]),
+        SpiTransaction::transfer(vec![0x44, 0x00], vec![0x44, 0x08]),
+        // Write LOCK (lock enable)
+        SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Read STATUS0 (lock check)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x00]),
+    ];
+    let cs_expectations: Vec<_> = (0..spi_expectations.len())
+        .flat_map(|_| {
+            [
+                PinTransaction::set(PinState::Low),
+                PinTransaction::set(PinState::High),
+            ]
+        })
+        .collect();
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut adc = Ade791x::new_ade7913(spi, cs);
+    let schedule = TemperatureSchedule {
+        interval: 2,
+        settling: 1,
+        window: 2,
+    };
+    adc.set_protection(false);
+    adc.set_temperature_schedule(Some(schedule)).unwrap();
+    adc.get_measurement().unwrap();
+    adc.get_measurement().unwrap();
+    adc.set_protection(true);
+    adc.set_temperature_schedule(Some(schedule)).unwrap();
+    adc.get_measurement().unwrap();
+    assert_eq!(adc.get_measurement(), Err(Error::LockFailed { device: 0 }));
+}
+
+#[test]
+fn temperature_schedule_write_failure() {
+    let burst_read = SpiTransaction::transfer(
+        vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        vec![0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97],
+    );
+    let spi_expectations = [
+        // Read TEMPOS (temperature offset)
+        SpiTransaction::transfer(vec![0xC4, 0x00], vec![0xC4, 0x5E]),
+        // Burst Read (from IWV to V2WV, voltage)
+        burst_read.clone(),
+        burst_read.clone(),
+        // Write LOCK (lock disable)
+        SpiTransaction::transfer(vec![0x50, 0x9C], vec![0x50, 0x9C]),
+        // Write/Read CONFIG (temperature enable, checked write failed)
+        SpiTransaction::transfer(vec![0x40, 0x08], vec![0x40, 0x08]),
+        SpiTransaction::transfer(vec![0x44, 0x00], vec![0x44, 0x00]),
+        // Write LOCK (lock enable)
+        SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Burst Read (from IWV to V2WV, voltage)
+        burst_read,
+        // Write LOCK (lock disable)
+        SpiTransaction::transfer(vec![0x50, 0x9C], vec![0x50, 0x9C]),
+        // Write/Read CONFIG (temperature enable, checked write)
+        SpiTransaction::transfer(vec![0x40, 0x08], vec![0x40, 0x08]),
+        SpiTransaction::transfer(vec![0x44, 0x00], vec![0x44, 0x08]),
+        // Write LOCK (lock enable)
+        SpiTransaction::transfer(vec![0x50, 0xCA], vec![0x50, 0xCA]),
+        // Read STATUS0 (lock check)
+        SpiTransaction::transfer(vec![0x4C, 0x00], vec![0x4C, 0x04]),
+    ];
+    let cs_expectations: Vec<_> = (0..spi_expectations.len())
+        .flat_map(|_| {
+            [
+                PinTransaction::set(PinState::Low),
+                PinTransaction::set(PinState::High),
+            ]
+        })
+        .collect();
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut adc = Ade791x::new_ade7913(spi, cs);
+    adc.set_temperature_schedule(Some(TemperatureSchedule {
+        interval: 2,
+        settling: 1,
+        window: 2,
+    }))
+    .unwrap();
+    adc.get_measurement().unwrap();
+    assert_eq!(
+        adc.get_measurement(),
+        Err(Error::RegisterContentMismatch {
+            device: 0,
+            register: Register::Config,
+            expected: 0x08,
+            actual: 0x00
+        })
+    );
+    assert!(!adc.expected_registers().config.temp_en);
+    assert_eq!(
+        adc.get_measurement().unwrap().aux,
+        MeasurementAux::Voltage(343.17712)
+    );
+    assert!(adc.expected_registers().config.temp_en);
+}
+
+#[test]
+fn temperature_schedule_every() {
+    let schedule = TemperatureSchedule::every(AdcFreqVal::KHz8, 60);
+    assert_eq!(schedule.interval, 8000 * 60);
+    assert_eq!(schedule.settling, 8);
+    assert_eq!(schedule.window, 8);
+    let schedule = TemperatureSchedule::every(AdcFreqVal::KHz8, u32::MAX);
+    assert_eq!(schedule.interval, u32::MAX);
+}
+
+#[test]
+fn temperature_schedule_not_permitted() {
+    let spi = SpiMock::new(&[]);
+    let cs = PinMock::new(&[]);
+    let mut adc = Ade791x::new_ade7912(spi, cs);
+    assert_eq!(
+        adc.set_temperature_schedule(Some(TemperatureSchedule::every(AdcFreqVal::KHz8, 60))),
+        Err(Error::TemperatureScheduleNotPermitted { device: 0 })
+    );
+}
+
+#[test]
+fn temperature_schedule_channels() {
+    let spi_expectations = [
+        // Read TEMPOS (temperature offset)
+        SpiTransaction::transfer(vec![0xC4, 0x00], vec![0xC4, 0x5E]),
+    ];
+    let cs_expectations = [
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ];
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut adc = Ade791x::new_ade7913(spi, cs);
+    let schedule = TemperatureSchedule::every(AdcFreqVal::KHz8, 60);
+    adc.set_channels(Channels::IwvV1wv).unwrap();
+    assert_eq!(
+        adc.set_temperature_schedule(Some(schedule)),
+        Err(Error::TemperatureScheduleNotPermitted { device: 0 })
+    );
+    adc.set_channels(Channels::All).unwrap();
+    adc.set_temperature_schedule(Some(schedule)).unwrap();
+    assert_eq!(
+        adc.set_channels(Channels::Iwv),
+        Err(Error::TemperatureScheduleNotPermitted { device: 0 })
+    );
+    assert_eq!(adc.channels(), [Channels::All]);
+}
+
+#[test]
+fn get_measurement_tempco() {
+    let spi_expectations = [
+        // Burst Read (from IWV to V2WV)
+        SpiTransaction::transfer(
+            vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            vec![0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97],
+        ),
+    ];
+    let cs_expectations = [
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ];
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut adc = Ade791x::new_ade7912(spi, cs);
+    let (current_gain, current_tempco) =
+        TempCo::fit_linear(&[(0.0, 1.02), (50.0, 0.97), (100.0, 0.92)], 25.0).unwrap();
+    let (voltage_gain, voltage_tempco) =
+        TempCo::fit_table(&[(50.0, 1.01), (0.0, 0.99), (25.0, 1.0)], 25.0).unwrap();
+    assert_eq!(current_gain, 0.995);
+    assert_eq!(
+        current_tempco,
+        TempCo::Linear {
+            t0: 25.0,
+            k: -0.0010050248
+        }
+    );
+    assert_eq!(voltage_gain, 1.0);
+    assert_eq!(
+        voltage_tempco,
+        TempCo::Table([(0.0, 0.99), (25.0, 1.0), (50.0, 1.01), (50.0, 1.01)])
+    );
+    adc.set_calibration(Calibration {
+        gain: CalibrationGain {
+            current: current_gain,
+            voltage: voltage_gain,
+            aux: None,
+        },
+        tempco: CalibrationTempCo {
+            current: current_tempco,
+            voltage: voltage_tempco,
+        },
+        ..Default::default()
+    });
+    assert_eq!(
+        adc.get_measurement().unwrap(),
+        Measurement {
+            current: 2.2987168,
+            voltage: 37.300293,
+            aux: MeasurementAux::Temperature(12.132141)
+        }
+    );
+}
+
+#[test]
+fn get_measurement_tempco_iwv() {
+    let spi_expectations = [
+        // Burst Read (IWV only)
+        SpiTransaction::transfer(vec![0x04, 0x00, 0x00, 0x00], vec![0x04, 0x05, 0xEC, 0xDF]),
+    ];
+    let cs_expectations = [
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ];
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut adc = Ade791x::new_ade7912(spi, cs);
+    adc.set_channels(Channels::Iwv).unwrap();
+    adc.set_calibration(Calibration {
+        tempco: CalibrationTempCo {
+            current: TempCo::Linear { t0: 25.0, k: 0.01 },
+            voltage: TempCo::Linear { t0: 25.0, k: 0.01 },
+        },
+        ..Default::default()
+    });
+    assert_eq!(adc.get_measurement().unwrap().current, 2.280772);
+}
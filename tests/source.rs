@@ -0,0 +1,70 @@
+use ade791x::record::*;
+use ade791x::*;
+use embedded_hal_mock::pin::{Mock as PinMock, State as PinState, Transaction as PinTransaction};
+use embedded_hal_mock::spi::{Mock as SpiMock, Transaction as SpiTransaction};
+
+/// Returns the mean current of the first ADC over the given number of samples.
+fn mean_current<M: MeasurementSource<N>, const N: usize>(
+    source: &mut M,
+    samples: usize,
+) -> Result<f32, M::Error> {
+    let mut sum = 0.0;
+    for _ in 0..samples {
+        sum += source.get_measurement()?[0].current;
+    }
+    Ok(sum / samples as f32)
+}
+
+#[test]
+fn measurement_source() {
+    let spi_expectations = [
+        // Burst Read (from IWV to V2WV)
+        SpiTransaction::transfer(
+            vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            vec![0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97],
+        ),
+        // Burst Read (from IWV to V2WV)
+        SpiTransaction::transfer(
+            vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            vec![0x04, 0x05, 0xEC, 0xDF, 0x06, 0x17, 0x1C, 0x37, 0xBE, 0x97],
+        ),
+    ];
+    let cs_expectations = [
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+        PinTransaction::set(PinState::Low),
+        PinTransaction::set(PinState::High),
+    ];
+    let spi = SpiMock::new(&spi_expectations);
+    let cs = PinMock::new(&cs_expectations);
+    let mut adc = Ade791x::new_ade7912(spi, cs);
+    assert_eq!(adc.sample_rate(), 8000);
+    assert_eq!(adc.chips(), [Chip::ADE7912]);
+    assert_eq!(adc.channels(), [Channels::All]);
+    let current = mean_current(&mut adc, 2).unwrap();
+    assert_eq!(current, 2.280772);
+    adc.set_channels(Channels::IwvV1wv).unwrap();
+
+    let mut buf = [0; 128];
+    let (mut recorder, mut len) =
+        Recorder::new(&adc.recording_header(false, false), &mut buf).unwrap();
+    let raw = RawMeasurement {
+        iwv: 388319,
+        v1wv: 399132,
+        v2wv: 3653271,
+    };
+    for _ in 0..2 {
+        len += recorder
+            .write_frame(&[Sample::from(raw)], &mut buf[len..])
+            .unwrap();
+    }
+    let mut replay = Replay::<1>::new(&buf[..len]).unwrap();
+    assert_eq!(replay.sample_rate(), 8000);
+    assert_eq!(replay.chips(), [Chip::ADE7912]);
+    assert_eq!(replay.channels(), [Channels::IwvV1wv]);
+    assert_eq!(mean_current(&mut replay, 2), Ok(current));
+    assert_eq!(
+        mean_current(&mut replay, 1),
+        Err(RecordError::EndOfRecording)
+    );
+}
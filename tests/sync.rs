@@ -0,0 +1,42 @@
+use ade791x::sync::*;
+
+#[test]
+fn record() {
+    let mut supervisor = SyncSupervisor::<3, 4>::default();
+    assert_eq!(supervisor.interval_ms(), 2000);
+    assert_eq!(supervisor.record([0, 1, -1]), [false; 3]);
+    assert_eq!(supervisor.interval_ms(), 4000);
+    assert_eq!(supervisor.record([0, 0, 4]), [false; 3]);
+    assert_eq!(supervisor.interval_ms(), 1000);
+    assert_eq!(supervisor.record([0, 0, -20]), [false; 3]);
+    assert_eq!(supervisor.interval_ms(), 250);
+    assert_eq!(supervisor.record([0, 1, 3]), [false, false, true]);
+    assert_eq!(supervisor.record([0, 0, 0]), [false, false, true]);
+    assert_eq!(supervisor.interval_ms(), 500);
+    supervisor.clear_alarms();
+    assert_eq!(supervisor.alarms(), [false; 3]);
+}
+
+#[test]
+fn stats() {
+    let mut supervisor = SyncSupervisor::<2, 3>::default();
+    assert_eq!(supervisor.stats(1), DriftStats::default());
+    for drift in [[0, 5], [0, -1], [0, 2], [0, 1]] {
+        supervisor.record(drift);
+    }
+    assert_eq!(
+        supervisor.history().copied().collect::<Vec<_>>(),
+        [[0, -1], [0, 2], [0, 1]]
+    );
+    assert_eq!(
+        supervisor.stats(1),
+        DriftStats {
+            count: 3,
+            min: -1,
+            max: 2,
+            mean: 0.6666667,
+            mean_abs: 1.3333334,
+            excursions: 1,
+        }
+    );
+}